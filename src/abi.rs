@@ -0,0 +1,86 @@
+use core::fmt::Display;
+
+use rk3588_rs::{
+    DRM_COMMAND_BASE, DRM_IOCTL_BASE, DrmVersion, RKNPU_ACTION, RKNPU_MEM_CREATE,
+    RKNPU_MEM_DESTROY, RKNPU_MEM_MAP, RKNPU_SUBMIT, RknpuMemCreate, RknpuMemDestroy, RknpuMemMap,
+    RknpuSubmit,
+};
+
+const IOC_READ: u32 = 2;
+const IOC_WRITE: u32 = 1;
+
+/// 按 Linux `_IOWR` 规则编码 ioctl 命令号
+const fn _iowr(ty: u8, nr: u32, size: usize) -> u32 {
+    ((IOC_READ | IOC_WRITE) << 30) | ((size as u32) << 16) | ((ty as u32) << 8) | nr
+}
+
+// 以下命令号与 C 驱动的 `rknpu-ioctl.h` 保持一致，用户态转接层可直接引用
+
+pub const DRM_IOCTL_RKNPU_ACTION: u32 = _iowr(DRM_IOCTL_BASE, DRM_COMMAND_BASE + RKNPU_ACTION, 8);
+pub const DRM_IOCTL_RKNPU_SUBMIT: u32 = _iowr(
+    DRM_IOCTL_BASE,
+    DRM_COMMAND_BASE + RKNPU_SUBMIT,
+    core::mem::size_of::<RknpuSubmit>(),
+);
+pub const DRM_IOCTL_RKNPU_MEM_CREATE: u32 = _iowr(
+    DRM_IOCTL_BASE,
+    DRM_COMMAND_BASE + RKNPU_MEM_CREATE,
+    core::mem::size_of::<RknpuMemCreate>(),
+);
+pub const DRM_IOCTL_RKNPU_MEM_MAP: u32 = _iowr(
+    DRM_IOCTL_BASE,
+    DRM_COMMAND_BASE + RKNPU_MEM_MAP,
+    core::mem::size_of::<RknpuMemMap>(),
+);
+pub const DRM_IOCTL_RKNPU_MEM_DESTROY: u32 = _iowr(
+    DRM_IOCTL_BASE,
+    DRM_COMMAND_BASE + RKNPU_MEM_DESTROY,
+    core::mem::size_of::<RknpuMemDestroy>(),
+);
+pub const DRM_IOCTL_VERSION: u32 = _iowr(DRM_IOCTL_BASE, 0x00, core::mem::size_of::<DrmVersion>());
+pub const DRM_IOCTL_RKNPU_MEM_SYNC: u32 = _iowr(
+    DRM_IOCTL_BASE,
+    DRM_COMMAND_BASE + 0x05,
+    core::mem::size_of::<RknpuMemDestroy>(),
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RkNpuIoctl {
+    DrmIoctlVersion,
+    RknpuAction,
+    RknpuMemCreate,
+    RknpuMemSync,
+    RknpuMemDestroy,
+    RknpuMemMap,
+    RknpuSubmit,
+}
+
+impl RkNpuIoctl {
+    pub const fn from_cmd(cmd: u32) -> Option<Self> {
+        match cmd {
+            DRM_IOCTL_VERSION => Some(Self::DrmIoctlVersion),
+            DRM_IOCTL_RKNPU_ACTION => Some(Self::RknpuAction),
+            DRM_IOCTL_RKNPU_MEM_CREATE => Some(Self::RknpuMemCreate),
+            DRM_IOCTL_RKNPU_MEM_SYNC => Some(Self::RknpuMemSync),
+            DRM_IOCTL_RKNPU_MEM_DESTROY => Some(Self::RknpuMemDestroy),
+            DRM_IOCTL_RKNPU_MEM_MAP => Some(Self::RknpuMemMap),
+            DRM_IOCTL_RKNPU_SUBMIT => Some(Self::RknpuSubmit),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RkNpuIoctl {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Self::DrmIoctlVersion => "DRM_IOCTL_VERSION",
+            Self::RknpuAction => "DRM_IOCTL_RKNPU_ACTION",
+            Self::RknpuMemCreate => "DRM_IOCTL_RKNPU_MEM_CREATE",
+            Self::RknpuMemSync => "DRM_IOCTL_RKNPU_MEM_SYNC",
+            Self::RknpuMemDestroy => "DRM_IOCTL_RKNPU_MEM_DESTROY",
+            Self::RknpuMemMap => "DRM_IOCTL_RKNPU_MEM_MAP",
+            Self::RknpuSubmit => "DRM_IOCTL_RKNPU_SUBMIT",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -1,4 +1,6 @@
-use super::types::RkBoard;
+use rk3588_rs::RKNPU_PC_DATA_EXTRA_AMOUNT;
+
+use super::types::{IntStatus, NpuCore, RkBoard};
 
 pub mod addresses {
     /// NPU 核心寄存器基地址
@@ -14,18 +16,30 @@ pub mod addresses {
     /// 每个核心的寄存器空间大小 (64KB)
     pub const NPU_CORE_SIZE: usize = 0x10000;
 
+    // 驱动按 `NPU0_BASE + index * NPU_CORE_SIZE` 访问各核心，须与设备树一致
+    const _: () = assert!(NPU1_BASE == NPU0_BASE + NPU_CORE_SIZE);
+    const _: () = assert!(NPU2_BASE == NPU0_BASE + 2 * NPU_CORE_SIZE);
+
     /// PMU1 (电源管理单元) 基地址
     pub const PMU1_BASE: usize = 0xFD8D8000;
 
+    /// PMU1 寄存器空间大小
+    pub const PMU1_SIZE: usize = 0x400;
+
     /// CRU (时钟复位单元) 基地址
     pub const CRU_BASE: usize = 0xFD7C0000;
 
+    /// CRU 寄存器空间大小
+    pub const CRU_SIZE: usize = 0x5C000;
+
     /// GPIO3 基地址
     pub const GPIO3_BASE: usize = 0xFEC40000;
 }
 
 /// CRU 软复位控制寄存器偏移和位定义
 pub mod cru_softrst {
+    use crate::types::NpuCore;
+
     /// NPU 软复位控制寄存器偏移
     pub const SOFTRST_CON_NPU: u32 = 0x0A00;
 
@@ -46,16 +60,232 @@ pub mod cru_softrst {
 
     /// 写使能掩码位移（RK 芯片特有的写保护机制）
     pub const WRITE_MASK_SHIFT: u32 = 16;
+
+    /// 总线复位类型
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ResetKind {
+        /// AXI 总线复位
+        Axi,
+        /// AHB 总线复位
+        Ahb,
+    }
+
+    /// 获取指定核心、指定总线的软复位位
+    pub const fn srst_bit(core: NpuCore, kind: ResetKind) -> u32 {
+        match (core, kind) {
+            (NpuCore::Npu0, ResetKind::Axi) => NPU0_AXI_SRST,
+            (NpuCore::Npu0, ResetKind::Ahb) => NPU0_AHB_SRST,
+            (NpuCore::Npu1, ResetKind::Axi) => NPU1_AXI_SRST,
+            (NpuCore::Npu1, ResetKind::Ahb) => NPU1_AHB_SRST,
+            (NpuCore::Npu2, ResetKind::Axi) => NPU2_AXI_SRST,
+            (NpuCore::Npu2, ResetKind::Ahb) => NPU2_AHB_SRST,
+        }
+    }
+}
+
+/// CRU NPU 时钟选择寄存器（CLKSEL_CON73）字段定义
+///
+/// 来自 RK3588 TRM / 内核 clk-rk3588.c 中的 `clk_npu_dsu0`：
+/// - Bit[9:7]: 父时钟选择 (gpll/cpll/aupll/npll/spll)
+/// - Bit[6:2]: 分频系数，实际分频为 `div + 1`
+///
+/// 输出频率 = 父 PLL 频率 / (div + 1)
+pub mod cru_clksel {
+    /// NPU 时钟选择寄存器偏移 (CLKSEL_CON73 = 0x300 + 73 * 4)
+    pub const CLKSEL_CON_NPU: u32 = 0x0424;
+
+    /// 父时钟选择位移
+    pub const NPU_MUX_SHIFT: u32 = 7;
+    /// 父时钟选择位宽
+    pub const NPU_MUX_WIDTH: u32 = 3;
+    /// 分频系数位移
+    pub const NPU_DIV_SHIFT: u32 = 2;
+    /// 分频系数位宽
+    pub const NPU_DIV_WIDTH: u32 = 5;
+
+    /// 各父时钟的频率 (Hz)，按选择值排列：gpll, cpll, aupll, npll, spll
+    ///
+    /// 为 RK3588 设备树中 assigned-clock-rates 给出的默认 PLL 频率
+    pub const NPU_PARENT_RATES: [u32; 5] = [
+        1_188_000_000,
+        1_500_000_000,
+        786_432_000,
+        850_000_000,
+        702_000_000,
+    ];
+
+    /// 根据 CLKSEL_CON73 的值计算 NPU 时钟频率 (Hz)
+    ///
+    /// 父时钟选择值超出范围时返回 `None`
+    pub const fn npu_clk_hz(clksel: u32) -> Option<u32> {
+        let mux = (clksel >> NPU_MUX_SHIFT) & ((1 << NPU_MUX_WIDTH) - 1);
+        let div = (clksel >> NPU_DIV_SHIFT) & ((1 << NPU_DIV_WIDTH) - 1);
+        if mux as usize >= NPU_PARENT_RATES.len() {
+            return None;
+        }
+        Some(NPU_PARENT_RATES[mux as usize] / (div + 1))
+    }
+
+    /// NPLL 在父时钟中的选择值
+    pub const NPU_MUX_NPLL: u32 = 3;
+
+    /// 重新配置 NPLL 期间 NPU 停靠的分频：gpll 二分频，594MHz，低于各板型的最高频率
+    pub const NPU_PARK_DIV: u32 = 1;
+
+    /// 构造写入 CLKSEL_CON73 的值（含高 16 位写使能掩码）
+    pub const fn clksel_value(mux: u32, div: u32) -> u32 {
+        let mask = (((1 << NPU_MUX_WIDTH) - 1) << NPU_MUX_SHIFT)
+            | (((1 << NPU_DIV_WIDTH) - 1) << NPU_DIV_SHIFT);
+        (mask << super::cru_softrst::WRITE_MASK_SHIFT)
+            | (mux << NPU_MUX_SHIFT)
+            | (div << NPU_DIV_SHIFT)
+    }
+
+    /// 在频率固定的父时钟（NPLL 除外）中查找能整除得到 `hz` 的 `(mux, div)`
+    pub const fn fixed_parent_for(hz: u32) -> Option<(u32, u32)> {
+        if hz == 0 {
+            return None;
+        }
+        let max_div = 1 << NPU_DIV_WIDTH;
+        let mut mux = 0;
+        while mux < NPU_PARENT_RATES.len() {
+            let rate = NPU_PARENT_RATES[mux];
+            if mux as u32 != NPU_MUX_NPLL && rate.is_multiple_of(hz) && rate / hz <= max_div {
+                return Some((mux as u32, rate / hz - 1));
+            }
+            mux += 1;
+        }
+        None
+    }
 }
 
+/// CRU NPLL 寄存器定义（RK3588 整数模式 PLL）
+///
+/// FOUT = FIN * m / (p * 2^s)，其中 FVCO = FIN * m / p 须在 2250MHz-4500MHz 之间
+pub mod cru_pll {
+    /// NPLL_CON0 偏移 (PLL_CON120 = 120 * 4)
+    pub const NPLL_CON0: u32 = 0x01E0;
+    /// NPLL_CON1 偏移
+    pub const NPLL_CON1: u32 = 0x01E4;
+    /// NPLL_CON2 偏移
+    pub const NPLL_CON2: u32 = 0x01E8;
+    /// NPLL_CON6 偏移
+    pub const NPLL_CON6: u32 = 0x01F8;
+
+    /// PLL 参考时钟 (xin24m)
+    pub const PLL_FIN_HZ: u64 = 24_000_000;
+    /// VCO 下限
+    pub const PLL_VCO_MIN_HZ: u64 = 2_250_000_000;
+    /// VCO 上限
+    pub const PLL_VCO_MAX_HZ: u64 = 4_500_000_000;
+
+    /// CON0: m [9:0]
+    pub const PLL_M_MASK: u32 = 0x3ff;
+    pub const PLL_M_MIN: u32 = 64;
+    /// CON1: p [5:0]
+    pub const PLL_P_MASK: u32 = 0x3f;
+    /// CON1: s [8:6]
+    pub const PLL_S_SHIFT: u32 = 6;
+    pub const PLL_S_MASK: u32 = 0x7;
+    /// CON1: 下电位
+    pub const PLL_PWRDOWN: u32 = 1 << 13;
+    /// CON2: 小数分频系数 k [15:0]
+    pub const PLL_K_MASK: u32 = 0xffff;
+    /// CON6: 锁定状态位
+    pub const PLL_LOCK: u32 = 1 << 15;
+
+    /// 等待 PLL 锁定的超时时间（微秒）
+    pub const PLL_LOCK_TIMEOUT_US: u64 = 1000;
+
+    /// PLL 分频参数
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct PllParams {
+        pub p: u32,
+        pub m: u32,
+        pub s: u32,
+    }
+
+    /// 查找整数模式下恰好输出 `hz` 的分频参数，无解时返回 `None`
+    pub const fn pll_params_for(hz: u32) -> Option<PllParams> {
+        let mut s = 0;
+        while s <= PLL_S_MASK {
+            let vco = (hz as u64) << s;
+            if vco >= PLL_VCO_MIN_HZ && vco <= PLL_VCO_MAX_HZ {
+                let mut p = 1;
+                while p <= PLL_P_MASK {
+                    let fref = vco * p as u64;
+                    if fref.is_multiple_of(PLL_FIN_HZ) {
+                        let m = fref / PLL_FIN_HZ;
+                        if m >= PLL_M_MIN as u64 && m <= PLL_M_MASK as u64 {
+                            return Some(PllParams { p, m: m as u32, s });
+                        }
+                    }
+                    p += 1;
+                }
+            }
+            s += 1;
+        }
+        None
+    }
+
+    /// 根据 NPLL_CON0/CON1 计算 PLL 输出频率 (Hz)，忽略小数部分
+    pub const fn pll_rate_hz(con0: u32, con1: u32) -> u32 {
+        let m = (con0 & PLL_M_MASK) as u64;
+        let p = (con1 & PLL_P_MASK) as u64;
+        let s = (con1 >> PLL_S_SHIFT) & PLL_S_MASK;
+        if p == 0 {
+            return 0;
+        }
+        ((PLL_FIN_HZ * m / p) >> s) as u32
+    }
+}
+
+/// CRU NPU 时钟门控寄存器（CLKGATE_CON29）定义
+///
+/// 写 1 关闭时钟，写 0 打开时钟；与软复位寄存器相同，高 16 位为写使能掩码
+pub mod cru_clkgate {
+    /// NPU 时钟门控寄存器偏移 (CLKGATE_CON29 = 0x800 + 29 * 4)
+    pub const CLKGATE_CON_NPU: u32 = 0x0874;
+
+    /// NPU 相关的时钟门控位
+    pub const NPU_CLK_GATE_MASK: u32 = 0xffff;
+}
+
+/// NBUF 基地址和大小的对齐要求 (4KB)
+pub const NBUF_ALIGN: u64 = 0x1000;
+
+/// 命令缓冲区中每条寄存器配置的字节数（64 位：地址与数据）
+pub const REGCMD_ENTRY_SIZE: usize = 8;
+
 /// 中断清除值
 pub const INT_CLEAR_VALUE: u32 = 0x1ffff;
 
+/// 任务完成后清除中断的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntClearStrategy {
+    /// 只写回观察到的 `int_status`，不影响其他挂起的中断
+    WriteObserved,
+    /// 写入 `INT_CLEAR_VALUE` 清除所有中断，用于写回状态值无法清除中断的板型
+    WriteAll,
+}
+
+impl IntClearStrategy {
+    /// 观察到 `int_status` 后写入 `int_clear` 的值
+    pub const fn clear_value(&self, int_status: u32) -> u32 {
+        match self {
+            Self::WriteObserved => int_status,
+            Self::WriteAll => INT_CLEAR_VALUE,
+        }
+    }
+}
+
 pub const RK3588_NPU_VERSION: u32 = 0x46495245;
 
 /// RKNPU 硬件配置
 #[derive(Debug, Clone, Copy)]
 pub struct RknpuConfig {
+    /// 配置对应的板型
+    pub board: RkBoard,
     /// 带宽优先级寄存器地址
     pub bw_priority_addr: u32,
     /// 带宽优先级寄存器长度
@@ -70,7 +300,7 @@ pub struct RknpuConfig {
     pub pc_task_number_mask: u32,
     /// PC 任务状态偏移
     pub pc_task_status_offset: u32,
-    /// PC DMA 控制
+    /// PC DMA 控制，非零时提交前写入 `pc_dma_base_addr` 并把该值写入 `pc_dma_ctrl`
     pub pc_dma_ctrl: u32,
     /// 带宽使能
     pub bw_enable: bool,
@@ -86,6 +316,44 @@ pub struct RknpuConfig {
     pub max_submit_number: u64,
     /// 核心掩码
     pub core_mask: u32,
+    /// 表示任务完成的中断位，`int_status` 中任意一位置位即认为任务完成
+    pub int_done_mask: u32,
+    /// NBUF SRAM 分配的对齐粒度
+    pub sram_alignment: u64,
+    /// `version` 寄存器的期望值，`initialize` 和自检据此确认硬件
+    ///
+    /// 目前各板型沿用 RK3588 的读数
+    pub expected_version: u32,
+    /// `version_num` 寄存器（次版本号）的期望值，`None` 表示不校验
+    ///
+    /// 同一 `version` 的不同芯片修订在次版本号上有差异，带宽控制、NBUF 等特性
+    /// 依赖具体修订。不一致时 `initialize` 只告警、不失败，让新修订仍可使用。
+    /// 目前还没有各板型的实测读数，均为 `None`，初始化时记录读到的值以便补充。
+    pub expected_version_num: Option<u32>,
+    /// 性能计数器寄存器相对核心基地址的偏移，`None` 表示该板型没有计数器
+    ///
+    /// 计数器为 32 位、在 NPU 核心时钟域计数，换算为时间需除以
+    /// `read_npu_clk_hz` 读出的频率
+    pub perf_counter_offset: Option<u32>,
+    /// 软复位置位后的保持时间（微秒）
+    ///
+    /// 复位需要在 AXI/AHB 时钟下保持若干个周期才能生效，NPU 时钟最低档时
+    /// 10us 仍有足够余量，不建议设置得更短
+    pub reset_assert_us: u32,
+    /// 释放复位后等待总线稳定的时间（微秒），不建议小于 5us
+    pub reset_settle_us: u32,
+    /// 提交可请求写入 `enable_mask` 寄存器的位，bit[6:0] 对应各功能块的使能
+    pub enable_mask_allowed: u32,
+    /// 让 PC 引擎进入 slave 模式时写入 `pc_data_addr` 的值
+    ///
+    /// 写错时 PC 引擎不会切换模式，任务永远不会完成；目前已知板型均为 0x1
+    pub slave_mode_value: u32,
+    /// `pc_op_en` 脉冲的高电平保持时间（微秒），0 表示两次写入之间不延时
+    pub pc_op_pulse_us: u32,
+    /// 等待路径和 `handle_irq` 确认任务完成时清除中断的方式
+    pub int_clear_strategy: IntClearStrategy,
+    /// `set_npu_clk_hz` 允许设置的最高 NPU 频率 (Hz)，取自设备树 OPP 表的最高档
+    pub max_npu_clk_hz: u32,
 }
 
 impl RknpuConfig {
@@ -96,6 +364,7 @@ impl RknpuConfig {
     /// - 40 位 DMA 地址
     /// - NBUF 支持
     pub const RK3562: Self = Self {
+        board: RkBoard::Rk3562,
         bw_priority_addr: 0x0,
         bw_priority_length: 0x0,
         dma_mask_bits: 40,
@@ -111,6 +380,18 @@ impl RknpuConfig {
         nbuf_size: 256 * 1024,
         max_submit_number: (1 << 16) - 1,
         core_mask: 0x1,
+        int_done_mask: IntStatus::DPU,
+        sram_alignment: NBUF_ALIGN,
+        expected_version: RK3588_NPU_VERSION,
+        expected_version_num: None,
+        perf_counter_offset: None,
+        reset_assert_us: 10,
+        reset_settle_us: 5,
+        enable_mask_allowed: 0x7f,
+        slave_mode_value: 0x1,
+        pc_op_pulse_us: 0,
+        max_npu_clk_hz: 1_000_000_000,
+        int_clear_strategy: IntClearStrategy::WriteObserved,
     };
     /// RK3568 配置
     ///
@@ -119,6 +400,7 @@ impl RknpuConfig {
     /// - 32 位 DMA 地址
     /// - 支持带宽控制
     pub const RK3568: Self = Self {
+        board: RkBoard::Rk3568,
         bw_priority_addr: 0xfe180008,
         bw_priority_length: 0x10,
         dma_mask_bits: 32,
@@ -134,6 +416,18 @@ impl RknpuConfig {
         nbuf_size: 0,
         max_submit_number: (1 << 12) - 1,
         core_mask: 0x1,
+        int_done_mask: IntStatus::DPU,
+        sram_alignment: NBUF_ALIGN,
+        expected_version: RK3588_NPU_VERSION,
+        expected_version_num: None,
+        perf_counter_offset: None,
+        reset_assert_us: 10,
+        reset_settle_us: 5,
+        enable_mask_allowed: 0x7f,
+        slave_mode_value: 0x1,
+        pc_op_pulse_us: 0,
+        max_npu_clk_hz: 1_000_000_000,
+        int_clear_strategy: IntClearStrategy::WriteObserved,
     };
     /// RK3583 配置
     ///
@@ -141,6 +435,7 @@ impl RknpuConfig {
     /// - 2 个 NPU 核心
     /// - 40 位 DMA 地址
     pub const RK3583: Self = Self {
+        board: RkBoard::Rk3583,
         bw_priority_addr: 0x0,
         bw_priority_length: 0x0,
         dma_mask_bits: 40,
@@ -156,6 +451,18 @@ impl RknpuConfig {
         nbuf_size: 0,
         max_submit_number: (1 << 12) - 1,
         core_mask: 0x3,
+        int_done_mask: IntStatus::DPU,
+        sram_alignment: NBUF_ALIGN,
+        expected_version: RK3588_NPU_VERSION,
+        expected_version_num: None,
+        perf_counter_offset: None,
+        reset_assert_us: 10,
+        reset_settle_us: 5,
+        enable_mask_allowed: 0x7f,
+        slave_mode_value: 0x1,
+        pc_op_pulse_us: 0,
+        max_npu_clk_hz: 1_000_000_000,
+        int_clear_strategy: IntClearStrategy::WriteObserved,
     };
     /// RK3588 配置
     ///
@@ -164,6 +471,7 @@ impl RknpuConfig {
     /// - 40 位 DMA 地址
     /// - 不支持带宽控制
     pub const RK3588: Self = Self {
+        board: RkBoard::Rk3588,
         bw_priority_addr: 0x0,
         bw_priority_length: 0x0,
         dma_mask_bits: 40,
@@ -179,6 +487,18 @@ impl RknpuConfig {
         nbuf_size: 0,
         max_submit_number: (1 << 12) - 1,
         core_mask: 0x7,
+        int_done_mask: IntStatus::DPU,
+        sram_alignment: NBUF_ALIGN,
+        expected_version: RK3588_NPU_VERSION,
+        expected_version_num: None,
+        perf_counter_offset: None,
+        reset_assert_us: 10,
+        reset_settle_us: 5,
+        enable_mask_allowed: 0x7f,
+        slave_mode_value: 0x1,
+        pc_op_pulse_us: 0,
+        max_npu_clk_hz: 1_000_000_000,
+        int_clear_strategy: IntClearStrategy::WriteObserved,
     };
     /// RV1106 配置
     ///
@@ -187,6 +507,7 @@ impl RknpuConfig {
     /// - 32 位 DMA 地址
     /// - 16 位任务编号
     pub const RV1106: Self = Self {
+        board: RkBoard::Rv1106,
         bw_priority_addr: 0x0,
         bw_priority_length: 0x0,
         dma_mask_bits: 32,
@@ -202,6 +523,18 @@ impl RknpuConfig {
         nbuf_size: 0,
         max_submit_number: (1 << 16) - 1,
         core_mask: 0x1,
+        int_done_mask: IntStatus::DPU,
+        sram_alignment: NBUF_ALIGN,
+        expected_version: RK3588_NPU_VERSION,
+        expected_version_num: None,
+        perf_counter_offset: None,
+        reset_assert_us: 10,
+        reset_settle_us: 5,
+        enable_mask_allowed: 0x7f,
+        slave_mode_value: 0x1,
+        pc_op_pulse_us: 0,
+        max_npu_clk_hz: 1_000_000_000,
+        int_clear_strategy: IntClearStrategy::WriteObserved,
     };
 
     /// 根据板型获取配置
@@ -225,7 +558,37 @@ impl RknpuConfig {
         }
     }
 
+    /// 计算 `pc_data_amount` 寄存器值
+    ///
+    /// 寄存器以 `pc_data_amount_scale` 为单位、减一编码，
+    /// 需要额外加上 PC 引擎自身的 `RKNPU_PC_DATA_EXTRA_AMOUNT` 条配置。
+    /// `regcfg_amount` 来自用户态，溢出时返回 `None`
+    pub const fn compute_data_amount(&self, regcfg_amount: u32) -> Option<u32> {
+        match regcfg_amount.checked_add(RKNPU_PC_DATA_EXTRA_AMOUNT) {
+            Some(amount) => Some(amount.div_ceil(self.pc_data_amount_scale) - 1),
+            None => None,
+        }
+    }
+
+    /// 判断 DMA 地址是否落在 `dma_mask_bits` 可寻址范围内
+    pub const fn dma_addr_fits(&self, addr: u64) -> bool {
+        self.dma_mask_bits >= u64::BITS || addr >> self.dma_mask_bits == 0
+    }
+
+    /// 计算指定核心的寄存器基地址
+    ///
+    /// `core_base` 为 NPU0 的寄存器基地址，各核心按 `NPU_CORE_SIZE` 依次排列
+    pub const fn core_base(&self, core_base: usize, core: NpuCore) -> usize {
+        core_base + core.index() * addresses::NPU_CORE_SIZE
+    }
+
+    /// 检查核心是否在板型的 `core_mask` 中
+    pub const fn is_available(&self, core: NpuCore) -> bool {
+        self.core_mask & core.mask_bit() != 0
+    }
+
     /// 检查核心是否可用
+    #[deprecated(note = "使用接受 `NpuCore` 的 `is_available`")]
     pub const fn is_core_available(&self, core: usize) -> bool {
         if core >= 3 {
             return false;
@@ -233,3 +596,214 @@ impl RknpuConfig {
         (self.core_mask & (1 << core)) != 0
     }
 }
+
+// 每个板型都必须有完整的配置：版本非零、核心掩码与核心数一致、复位数不少于核心数。
+// 新增 `RkBoard` 变体时 `RkBoard::index` 的穷举匹配会提醒同步更新 `RkBoard::ALL`。
+const _: () = {
+    let mut i = 0;
+    while i < RkBoard::ALL.len() {
+        let board = RkBoard::ALL[i];
+        assert!(board.index() == i);
+        let config = RknpuConfig::from_board(board);
+        assert!(config.board.index() == i);
+        assert!(config.expected_version != 0);
+        assert!(config.core_mask == board.core_mask());
+        assert!(config.core_mask.count_ones() as usize == board.num_cores());
+        assert!(config.num_resets >= board.num_cores());
+        i += 1;
+    }
+};
+
+/// `pc_task_control` 寄存器值构造器
+///
+/// 寄存器低 `pc_task_number_bits` 位为任务数，其上为模式位，模式位最低位为 ping-pong 使能
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcTaskControl {
+    mode: u32,
+    pingpong: bool,
+    task_number: u32,
+}
+
+impl PcTaskControl {
+    /// C 驱动使用的默认模式位
+    pub const DEFAULT_MODE: u32 = 0x6;
+
+    pub const fn new() -> Self {
+        Self {
+            mode: Self::DEFAULT_MODE,
+            pingpong: false,
+            task_number: 0,
+        }
+    }
+
+    /// 设置模式位（不含 ping-pong 使能位）
+    pub const fn mode(mut self, mode: u32) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub const fn pingpong(mut self, enabled: bool) -> Self {
+        self.pingpong = enabled;
+        self
+    }
+
+    pub const fn task_number(mut self, task_number: u32) -> Self {
+        self.task_number = task_number;
+        self
+    }
+
+    /// 按板型的任务数位宽编码
+    ///
+    /// 任务数超出 `pc_task_number_mask`（会溢出到模式位）、模式位占用了 ping-pong 位
+    /// 或移位溢出时返回 `None`
+    pub const fn encode(&self, config: &RknpuConfig) -> Option<u32> {
+        if self.task_number & !config.pc_task_number_mask != 0 || self.mode & 1 != 0 {
+            return None;
+        }
+        let mode = self.mode | self.pingpong as u32;
+        if mode.leading_zeros() < config.pc_task_number_bits {
+            return None;
+        }
+        Some((mode << config.pc_task_number_bits) | self.task_number)
+    }
+}
+
+impl Default for PcTaskControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pc_task_control_words_per_board() {
+        let control = PcTaskControl::new().task_number(3);
+        let pingpong = control.pingpong(true);
+
+        let rk3588 = RknpuConfig::from_board(RkBoard::Rk3588);
+        assert_eq!(control.encode(&rk3588), Some(0x6003));
+        assert_eq!(pingpong.encode(&rk3588), Some(0x7003));
+
+        let rk3562 = RknpuConfig::from_board(RkBoard::Rk3562);
+        assert_eq!(control.encode(&rk3562), Some(0x6_0003));
+        assert_eq!(pingpong.encode(&rk3562), Some(0x7_0003));
+
+        for board in RkBoard::ALL {
+            let config = RknpuConfig::from_board(board);
+            let word = pingpong.encode(&config).unwrap();
+            assert_eq!(word & config.pc_task_number_mask, 3, "{:?}", board);
+            assert_eq!(word >> config.pc_task_number_bits, 0x7, "{:?}", board);
+        }
+    }
+
+    #[test]
+    fn pc_task_control_rejects_overflowing_fields() {
+        let rk3588 = RknpuConfig::from_board(RkBoard::Rk3588);
+        let max = PcTaskControl::new().task_number(rk3588.pc_task_number_mask);
+        assert_eq!(max.encode(&rk3588), Some(0x6fff));
+        assert_eq!(max.task_number(0x1000).encode(&rk3588), None);
+        // 模式位不能占用 ping-pong 位
+        assert_eq!(PcTaskControl::new().mode(0x7).encode(&rk3588), None);
+        // 模式位左移后溢出
+        assert_eq!(PcTaskControl::new().mode(0x10_0000).encode(&rk3588), None);
+        // 任务数超出寄存器字段
+        for board in RkBoard::ALL {
+            let config = RknpuConfig::from_board(board);
+            assert_eq!(
+                PcTaskControl::new().task_number(u32::MAX).encode(&config),
+                None,
+                "{:?}",
+                board
+            );
+        }
+    }
+
+    #[test]
+    fn every_board_has_a_complete_config() {
+        for (i, board) in RkBoard::ALL.into_iter().enumerate() {
+            assert_eq!(board.index(), i);
+            let config = RknpuConfig::from_board(board);
+            assert_eq!(config.board, board);
+            assert_ne!(config.expected_version, 0, "{:?}", board);
+            // 核心从 Npu0 起连续编号
+            assert_eq!(
+                config.core_mask,
+                (1 << board.num_cores()) - 1,
+                "{:?}",
+                board
+            );
+            assert_eq!(config.num_cores(), board.num_cores(), "{:?}", board);
+            assert!(config.is_available(NpuCore::Npu0), "{:?}", board);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn is_available_agrees_with_is_core_available() {
+        for board in RkBoard::ALL {
+            let config = RknpuConfig::from_board(board);
+            for core in [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2] {
+                assert_eq!(
+                    config.is_available(core),
+                    config.is_core_available(core.index()),
+                    "{:?} {:?}",
+                    board,
+                    core
+                );
+            }
+            assert!(!config.is_core_available(3));
+        }
+    }
+
+    #[test]
+    fn dma_addr_fits_straddles_each_boards_mask() {
+        for board in RkBoard::ALL {
+            let config = RknpuConfig::from_board(board);
+            let limit = 1u64 << config.dma_mask_bits;
+            assert!(config.dma_addr_fits(0), "{:?}", board);
+            assert!(config.dma_addr_fits(limit - 1), "{:?}", board);
+            assert!(!config.dma_addr_fits(limit), "{:?}", board);
+            assert!(!config.dma_addr_fits(u64::MAX), "{:?}", board);
+        }
+    }
+
+    #[test]
+    fn srst_bit_maps_each_core_and_bus() {
+        use cru_softrst::*;
+
+        let cases = [
+            (NpuCore::Npu0, ResetKind::Axi, 0),
+            (NpuCore::Npu0, ResetKind::Ahb, 1),
+            (NpuCore::Npu1, ResetKind::Axi, 2),
+            (NpuCore::Npu1, ResetKind::Ahb, 3),
+            (NpuCore::Npu2, ResetKind::Axi, 4),
+            (NpuCore::Npu2, ResetKind::Ahb, 5),
+        ];
+        let mut seen = 0u32;
+        for (core, kind, bit) in cases {
+            assert_eq!(srst_bit(core, kind), bit, "{:?} {:?}", core, kind);
+            seen |= 1 << bit;
+        }
+        // 各位互不重叠，且都在写使能掩码之下
+        assert_eq!(seen, 0x3f);
+        assert!(seen < 1 << WRITE_MASK_SHIFT);
+    }
+
+    #[test]
+    fn core_base_matches_documented_addresses() {
+        const NPU2: usize = RknpuConfig::RK3588.core_base(addresses::NPU0_BASE, NpuCore::Npu2);
+        assert_eq!(NPU2, addresses::NPU2_BASE);
+
+        let config = RknpuConfig::from_board(RkBoard::Rk3588);
+        for (core, base) in [
+            (NpuCore::Npu0, addresses::NPU0_BASE),
+            (NpuCore::Npu1, addresses::NPU1_BASE),
+            (NpuCore::Npu2, addresses::NPU2_BASE),
+        ] {
+            assert_eq!(config.core_base(addresses::NPU0_BASE, core), base);
+        }
+    }
+}
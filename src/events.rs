@@ -0,0 +1,29 @@
+use crate::types::NpuCore;
+
+/// NPU 结构化事件
+///
+/// 与日志输出并行产生，便于操作系统直接汇总 NPU 指标而无需解析日志文本。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NpuEvent {
+    /// 任务已提交到硬件
+    JobSubmitted { core: NpuCore, task_number: u32 },
+    /// 任务完成
+    ///
+    /// `elapsed_us` 为提交到完成的耗时，无法测量时为 `None`
+    JobCompleted {
+        core: NpuCore,
+        int_status: u32,
+        elapsed_us: Option<u64>,
+    },
+    /// 任务因错误中断失败，`int_status` 含 DMA 读写错误位
+    JobFailed { core: NpuCore, int_status: u32 },
+    /// 等待任务完成超时
+    JobTimeout { core: NpuCore, timeout_ms: u32 },
+    /// 执行了复位，`power_cycle` 表示是否对电源域进行了下电/上电
+    ResetPerformed { power_cycle: bool },
+}
+
+/// 结构化事件接收者
+pub trait EventSink: Sync {
+    fn on_event(&self, event: NpuEvent);
+}
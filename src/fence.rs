@@ -0,0 +1,16 @@
+use crate::types::RkNpuResult;
+
+/// 不透明的 fence 标识，具体含义由 [`FenceProvider`] 决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FenceId(pub u64);
+
+/// 由宿主系统实现的 fence 原语
+///
+/// 用于把 NPU 任务完成事件传递给图形栈等其他驱动，使其无需轮询本驱动。
+/// 没有 fence 原语的系统不安装该 trait 即可，不会产生额外开销。
+pub trait FenceProvider: Sync {
+    /// 创建一个未触发的 fence
+    fn create_fence(&self) -> RkNpuResult<FenceId>;
+    /// 触发 fence，`result` 为任务的执行结果
+    fn signal_fence(&self, fence: FenceId, result: RkNpuResult<()>);
+}
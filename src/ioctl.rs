@@ -1,16 +1,15 @@
 use log::{debug, info};
-use memory_addr::{PhysAddr, VirtAddr};
 use rk3588_rs::{
-    DrmVersion, RknpuAction, RknpuMemSync,
-    RknpuSubmit,
+    DrmVersion, RknpuAction, RknpuMemCreate, RknpuMemDestroy, RknpuMemSync, RknpuSubmit,
 };
 
 use crate::{
     RknpuDev,
-    types::{RkNpuError, RkNpuIoctl, RkNpuResult},
+    abi::RkNpuIoctl,
+    types::{RkNpuError, RkNpuResult},
 };
 
-pub fn rknpu_ioctl(rknpu: &RknpuDev, rknpu_cmd: Option<RkNpuIoctl>, arg: usize, dma_to_kernel: fn(PhysAddr) -> VirtAddr) -> RkNpuResult<()> {
+pub fn rknpu_ioctl(rknpu: &RknpuDev, rknpu_cmd: Option<RkNpuIoctl>, arg: usize) -> RkNpuResult<()> {
     debug!("rknpu ioctl => cmd: {:?}, arg: {:#x}", rknpu_cmd, arg);
     match rknpu_cmd {
         Some(RkNpuIoctl::DrmIoctlVersion) => {
@@ -19,32 +18,11 @@ pub fn rknpu_ioctl(rknpu: &RknpuDev, rknpu_cmd: Option<RkNpuIoctl>, arg: usize,
             drm_ver.version_minor = 0;
             drm_ver.version_patchlevel = 0;
 
-            if !drm_ver.name.is_null() && drm_ver.name_len > 0 {
-                let name = b"rknpu\0";
-                let copy_len = core::cmp::min(name.len(), drm_ver.name_len);
-                unsafe {
-                    core::ptr::copy_nonoverlapping(name.as_ptr(), drm_ver.name, copy_len);
-                }
-                drm_ver.name_len = copy_len;
-            }
-
-            if !drm_ver.date.is_null() && drm_ver.date_len > 0 {
-                let date = b"20251023\0";
-                let copy_len = core::cmp::min(date.len(), drm_ver.date_len);
-                unsafe {
-                    core::ptr::copy_nonoverlapping(date.as_ptr(), drm_ver.date, copy_len);
-                }
-                drm_ver.date_len = copy_len;
-            }
-
-            if !drm_ver.desc.is_null() && drm_ver.desc_len > 0 {
-                let desc = b"Rockchip NPU Simulated\0";
-                let copy_len = core::cmp::min(desc.len(), drm_ver.desc_len);
-                unsafe {
-                    core::ptr::copy_nonoverlapping(desc.as_ptr(), drm_ver.desc, copy_len);
-                }
-                drm_ver.desc_len = copy_len;
-            }
+            copy_version_string(rknpu, drm_ver.name, &mut drm_ver.name_len, b"rknpu\0")?;
+            copy_version_string(rknpu, drm_ver.date, &mut drm_ver.date_len, b"20251023\0")?;
+            let mut desc = rknpu.drm_desc();
+            desc.push('\0');
+            copy_version_string(rknpu, drm_ver.desc, &mut drm_ver.desc_len, desc.as_bytes())?;
             Ok(())
         }
         Some(RkNpuIoctl::RknpuAction) => {
@@ -53,7 +31,15 @@ pub fn rknpu_ioctl(rknpu: &RknpuDev, rknpu_cmd: Option<RkNpuIoctl>, arg: usize,
         }
         Some(RkNpuIoctl::RknpuSubmit) => {
             let submit = unsafe { &mut *(arg as *mut RknpuSubmit) };
-            rknpu.rknpu_submit_ioctl(submit, dma_to_kernel)
+            rknpu.rknpu_submit_ioctl(submit)
+        }
+        Some(RkNpuIoctl::RknpuMemCreate) => {
+            let mem_create = unsafe { &mut *(arg as *mut RknpuMemCreate) };
+            rknpu.rknpu_mem_create_ioctl(mem_create)
+        }
+        Some(RkNpuIoctl::RknpuMemDestroy) => {
+            let mem_destroy = unsafe { &*(arg as *const RknpuMemDestroy) };
+            rknpu.rknpu_mem_destroy_ioctl(mem_destroy)
         }
         Some(RkNpuIoctl::RknpuMemSync) => {
             let mem_sync = unsafe { &mut *(arg as *mut RknpuMemSync) };
@@ -62,3 +48,122 @@ pub fn rknpu_ioctl(rknpu: &RknpuDev, rknpu_cmd: Option<RkNpuIoctl>, arg: usize,
         _ => Err(RkNpuError::InvalidInput),
     }
 }
+
+/// 把版本字符串复制到用户缓冲区，并把 `len` 更新为实际复制的字节数
+///
+/// 最多复制 `min(src.len(), *len)` 字节，且只在该范围通过
+/// [`RknpuDev::user_buffer`] 校验后才写入；缓冲区为空或长度为 0 时跳过。
+fn copy_version_string(
+    rknpu: &RknpuDev,
+    dst: *mut u8,
+    len: &mut usize,
+    src: &[u8],
+) -> RkNpuResult<()> {
+    if dst.is_null() || *len == 0 {
+        return Ok(());
+    }
+    let copy_len = core::cmp::min(src.len(), *len);
+    let dst = rknpu.user_buffer(dst as usize, copy_len)?;
+    unsafe {
+        core::ptr::copy_nonoverlapping(src.as_ptr(), dst, copy_len);
+    }
+    *len = copy_len;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use memory_addr::{PhysAddr, VirtAddr};
+    use std::boxed::Box;
+
+    use super::*;
+    use crate::{memory::NpuAllocator, types::RkBoard};
+
+    fn identity(addr: PhysAddr) -> VirtAddr {
+        VirtAddr::from(addr.as_usize())
+    }
+
+    /// 把用户地址 `[user, user + len)` 映射到内核缓冲区 `kernel` 的分配器
+    struct Window {
+        user: usize,
+        kernel: usize,
+        len: usize,
+    }
+
+    impl NpuAllocator for Window {
+        fn create_handle(&self, _size: usize) -> RkNpuResult<(u32, u64, u64)> {
+            Err(RkNpuError::NotSupported)
+        }
+
+        fn destroy_handle(&self, _handle: u32) -> bool {
+            false
+        }
+
+        fn get_handle(&self, _handle: u32) -> RkNpuResult<(u64, usize)> {
+            Err(RkNpuError::NotSupported)
+        }
+
+        fn user_to_kernel_addr(&self, user_addr: usize) -> RkNpuResult<VirtAddr> {
+            if !(self.user..self.user + self.len).contains(&user_addr) {
+                return Err(RkNpuError::InvalidInput);
+            }
+            Ok(VirtAddr::from(self.kernel + (user_addr - self.user)))
+        }
+    }
+
+    #[test]
+    fn version_copy_bounded_by_source_and_declared_length() {
+        let rknpu = RknpuDev::new(0, 0, 0, RkBoard::Rk3588, identity);
+        let mut buf = [0xaau8; 16];
+
+        // 声明的长度大于字符串时只复制字符串
+        let mut len = buf.len();
+        copy_version_string(&rknpu, buf.as_mut_ptr(), &mut len, b"rknpu\0").unwrap();
+        assert_eq!(len, 6);
+        assert_eq!(&buf[..6], b"rknpu\0");
+        assert!(buf[6..].iter().all(|&b| b == 0xaa));
+
+        // 声明的长度小于字符串时截断
+        let mut buf = [0xaau8; 16];
+        let mut len = 3;
+        copy_version_string(&rknpu, buf.as_mut_ptr(), &mut len, b"rknpu\0").unwrap();
+        assert_eq!(len, 3);
+        assert_eq!(&buf[..4], b"rkn\xaa");
+
+        // 空指针或长度为 0 时不写入
+        let mut len = 0;
+        copy_version_string(&rknpu, buf.as_mut_ptr(), &mut len, b"rknpu\0").unwrap();
+        assert_eq!(len, 0);
+        let mut len = 16;
+        copy_version_string(&rknpu, core::ptr::null_mut(), &mut len, b"rknpu\0").unwrap();
+        assert_eq!(len, 16);
+    }
+
+    #[test]
+    fn version_copy_rejects_declared_length_beyond_mapped_buffer() {
+        let kernel = Box::leak(Box::new([0xaau8; 4]));
+        let mut rknpu = RknpuDev::new(0, 0, 0, RkBoard::Rk3588, identity);
+        rknpu.set_allocator(Box::leak(Box::new(Window {
+            user: 0x1000,
+            kernel: kernel.as_mut_ptr() as usize,
+            len: kernel.len(),
+        })));
+
+        // 用户声明 64 字节，实际只映射了 4 字节
+        let mut len = 64;
+        assert_eq!(
+            copy_version_string(&rknpu, 0x1000 as *mut u8, &mut len, b"rknpu\0"),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(len, 64);
+        assert_eq!(*kernel, [0xaa; 4]);
+
+        // 落在映射范围内的复制经由内核地址写入
+        let mut len = 4;
+        copy_version_string(&rknpu, 0x1000 as *mut u8, &mut len, b"rknpu\0").unwrap();
+        assert_eq!(len, 4);
+        assert_eq!(kernel, b"rknp");
+    }
+}
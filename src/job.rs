@@ -0,0 +1,183 @@
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::{
+    fence::FenceId,
+    types::{NpuCore, RkNpuError, RkNpuResult},
+};
+
+/// 同时在途的最大任务数，任务表满时提交返回 `Busy`
+pub const MAX_INFLIGHT: usize = 8;
+
+/// 任务标识，0 保留表示空槽位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobId(pub u32);
+
+/// 槽位被占用但内容尚未发布（登记或结束过程中）
+const SLOT_RESERVED: u32 = u32::MAX;
+/// 槽位字中核心编号所占的位数
+const CORE_BITS: u32 = 2;
+const CORE_MASK: u32 = (1 << CORE_BITS) - 1;
+
+/// 单个在途任务
+///
+/// `entry` 为 0 表示空闲，否则为 `(id << CORE_BITS) | core`，
+/// 用一个原子字同时发布任务号和核心，读者不会看到不一致的组合。
+struct JobSlot {
+    entry: AtomicU32,
+    fence: AtomicU64,
+}
+
+impl JobSlot {
+    const fn new() -> Self {
+        Self {
+            entry: AtomicU32::new(0),
+            fence: AtomicU64::new(0),
+        }
+    }
+
+    fn load(&self) -> Option<(JobId, NpuCore)> {
+        match self.entry.load(Ordering::Acquire) {
+            0 | SLOT_RESERVED => None,
+            entry => Some((
+                JobId(entry >> CORE_BITS),
+                NpuCore::from_index((entry & CORE_MASK) as usize)?,
+            )),
+        }
+    }
+}
+
+/// 在途任务表
+pub(crate) struct JobTable {
+    slots: [JobSlot; MAX_INFLIGHT],
+    next_id: AtomicU32,
+}
+
+impl JobTable {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { JobSlot::new() }; MAX_INFLIGHT],
+            next_id: AtomicU32::new(1),
+        }
+    }
+
+    fn alloc_id(&self) -> JobId {
+        loop {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) & (u32::MAX >> CORE_BITS);
+            if id != 0 {
+                return JobId(id);
+            }
+        }
+    }
+
+    /// 在指定核心上登记一个新任务
+    ///
+    /// 任务表已满时返回 `Busy`，不会覆盖已有的在途任务。
+    pub fn begin(&self, core: NpuCore, fence: Option<FenceId>) -> RkNpuResult<JobId> {
+        let slot = self
+            .slots
+            .iter()
+            .find(|slot| {
+                slot.entry
+                    .compare_exchange(0, SLOT_RESERVED, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            })
+            .ok_or(RkNpuError::Busy)?;
+
+        let id = self.alloc_id();
+        slot.fence
+            .store(fence.map_or(0, |f| f.0), Ordering::Relaxed);
+        slot.entry
+            .store((id.0 << CORE_BITS) | core.index() as u32, Ordering::Release);
+        Ok(id)
+    }
+
+    /// 结束指定核心上最早登记的在途任务，返回任务及其 fence
+    ///
+    /// 同一任务只会被取出一次，轮询路径与中断路径不会重复完成。
+    pub fn finish(&self, core: NpuCore) -> Option<(JobId, Option<FenceId>)> {
+        loop {
+            let (slot, job) = self
+                .slots
+                .iter()
+                .filter_map(|slot| Some((slot, slot.load()?)))
+                .filter(|(_, (_, c))| *c == core)
+                .map(|(slot, (job, _))| (slot, job))
+                .min_by_key(|(_, job)| job.0)?;
+
+            let entry = (job.0 << CORE_BITS) | core.index() as u32;
+            if slot
+                .entry
+                .compare_exchange(entry, SLOT_RESERVED, Ordering::AcqRel, Ordering::Relaxed)
+                .is_err()
+            {
+                // 被其他路径抢先结束，重新查找
+                continue;
+            }
+            let fence = slot.fence.swap(0, Ordering::Relaxed);
+            slot.entry.store(0, Ordering::Release);
+            return Some((job, (fence != 0).then_some(FenceId(fence))));
+        }
+    }
+
+    /// 获取指定核心上最早登记的在途任务
+    pub fn current(&self, core: NpuCore) -> Option<JobId> {
+        self.slots
+            .iter()
+            .filter_map(JobSlot::load)
+            .filter(|(_, c)| *c == core)
+            .map(|(job, _)| job)
+            .min_by_key(|job| job.0)
+    }
+
+    /// 已提交但尚未完成的任务数
+    pub fn pending(&self) -> usize {
+        self.slots.iter().filter_map(JobSlot::load).count()
+    }
+
+    /// 指定核心上已提交但尚未完成的任务数
+    pub fn pending_on(&self, core: NpuCore) -> usize {
+        self.slots
+            .iter()
+            .filter_map(JobSlot::load)
+            .filter(|(_, c)| *c == core)
+            .count()
+    }
+
+    fn slot_of(&self, job: JobId) -> Option<&JobSlot> {
+        self.slots
+            .iter()
+            .find(|slot| slot.load().is_some_and(|(id, _)| id == job))
+    }
+
+    /// 查找在途任务，返回其 fence；任务不在途时返回 `None`
+    pub fn lookup(&self, job: JobId) -> Option<Option<FenceId>> {
+        let fence = self.slot_of(job)?.fence.load(Ordering::Relaxed);
+        Some((fence != 0).then_some(FenceId(fence)))
+    }
+
+    /// 为在途任务补挂 fence
+    ///
+    /// 返回 `Ok(())` 表示 fence 已挂上，任务完成时会被触发；
+    /// 已有 fence 时返回 `Err(Some(existing))`；任务已完成时返回 `Err(None)`，
+    /// 此时由调用者自行触发 fence。
+    pub fn attach_fence(&self, job: JobId, fence: FenceId) -> Result<(), Option<FenceId>> {
+        let slot = self.slot_of(job).ok_or(None)?;
+        if let Err(existing) =
+            slot.fence
+                .compare_exchange(0, fence.0, Ordering::AcqRel, Ordering::Acquire)
+        {
+            return Err(Some(FenceId(existing)));
+        }
+        if slot.load().is_some_and(|(id, _)| id == job) {
+            return Ok(());
+        }
+        // 任务已完成：fence 若未被 finish 取走则收回
+        match slot
+            .fence
+            .compare_exchange(fence.0, 0, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => Err(None),
+            Err(_) => Ok(()),
+        }
+    }
+}
@@ -2,12 +2,33 @@
 
 extern crate alloc;
 
+/// 提交/等待热路径上的调试日志
+///
+/// 仅在启用 `verbose-trace` 特性时展开为 `debug!`，否则整条语句（包括参数求值）
+/// 都会被编译掉。
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "verbose-trace")]
+        log::debug!($($arg)*);
+    };
+}
+
+pub mod abi;
 pub mod configs;
+pub mod events;
+pub mod fence;
+mod ioctl;
+pub mod job;
+pub mod memory;
+pub mod mmio;
+pub mod power;
 pub mod registers;
 mod rknpu_dev;
+pub mod sched;
+mod sram;
+pub mod stats;
+pub mod time;
 pub mod types;
-mod ioctl;
-pub mod memory;
 
+pub use ioctl::rknpu_ioctl;
 pub use rknpu_dev::*;
-pub use ioctl::rknpu_ioctl;
\ No newline at end of file
@@ -9,3 +9,12 @@ pub trait NpuAllocator {
     fn get_handle(&self, handle: u32) -> RkNpuResult<(u64, usize)>;
     fn user_to_kernel_addr(&self, user_addr: usize) -> RkNpuResult<VirtAddr>;
 }
+
+/// 由宿主系统实现的缓冲区查询
+///
+/// 提交时用于确认任务描述符等 DMA 地址落在一块已分配的缓冲区内，
+/// 未安装时驱动只做整数溢出检查。
+pub trait BufferResolver: Sync {
+    /// 返回从 `dma_addr` 到其所在缓冲区末尾的字节数，地址不属于任何缓冲区时返回 `None`
+    fn buffer_len(&self, dma_addr: u64) -> Option<usize>;
+}
@@ -0,0 +1,206 @@
+/// 32 位寄存器访问接口
+///
+/// 驱动的所有寄存器读写都经过该接口，`offset` 为相对寄存器区域起始处的字节偏移。
+/// 默认使用 [`DirectMmio`] 直接访问映射后的虚拟地址；在虚拟化客户机中可替换为
+/// 经由 hypercall 或 mailbox 转发的实现。
+pub trait Mmio: Sync {
+    fn read32(&self, offset: usize) -> u32;
+    fn write32(&self, offset: usize, value: u32);
+}
+
+/// 直接解引用映射地址的寄存器访问，编译后与手写的 volatile 读写相同
+#[derive(Debug, Clone, Copy)]
+pub struct DirectMmio {
+    base: usize,
+}
+
+impl DirectMmio {
+    /// `base` 为寄存器区域已映射的虚拟地址
+    pub const fn new(base: usize) -> Self {
+        Self { base }
+    }
+}
+
+impl Mmio for DirectMmio {
+    #[inline(always)]
+    fn read32(&self, offset: usize) -> u32 {
+        unsafe { core::ptr::read_volatile((self.base + offset) as *const u32) }
+    }
+
+    #[inline(always)]
+    fn write32(&self, offset: usize, value: u32) {
+        unsafe { core::ptr::write_volatile((self.base + offset) as *mut u32, value) }
+    }
+}
+
+/// 测试用的寄存器模拟
+#[cfg(test)]
+pub(crate) mod mock {
+    extern crate std;
+
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use std::{boxed::Box, collections::VecDeque, sync::Mutex, vec::Vec};
+
+    use super::Mmio;
+    use crate::{
+        configs::addresses::NPU_CORE_SIZE,
+        registers::{RKNPU_CRU_REGISTERS_SIZE, npu_reg},
+    };
+
+    /// 以内存数组模拟的寄存器区域，记录每一次写入
+    ///
+    /// NPU 区域按硬件语义处理 `int_clear`：写 1 清除 `int_status`/`int_raw_status`
+    /// 的对应位，不保存写入值。
+    pub struct MockMmio {
+        regs: Vec<AtomicU32>,
+        npu: bool,
+        writes: Mutex<Vec<(usize, u32)>>,
+        /// 按顺序记录的读取偏移
+        reads: Mutex<Vec<usize>>,
+        /// 忽略写入的寄存器偏移
+        stuck: Mutex<Vec<usize>>,
+        /// 下一次读取 `int_status` 后到达的中断 `(int_status 偏移, 中断位)`
+        arriving: Mutex<Option<(usize, u32)>>,
+        /// 下一次启动任务后到达的中断 `(pc_op_en 偏移, 中断位)`
+        on_start: Mutex<Option<(usize, u32)>>,
+        /// 按读取顺序依次返回的寄存器值 `(偏移, 剩余的值)`
+        scripted: Mutex<Vec<(usize, VecDeque<u32>)>>,
+    }
+
+    impl MockMmio {
+        fn leak(size: usize, npu: bool) -> &'static Self {
+            Box::leak(Box::new(Self {
+                regs: (0..size / 4).map(|_| AtomicU32::new(0)).collect(),
+                npu,
+                writes: Mutex::new(Vec::new()),
+                reads: Mutex::new(Vec::new()),
+                stuck: Mutex::new(Vec::new()),
+                arriving: Mutex::new(None),
+                on_start: Mutex::new(None),
+                scripted: Mutex::new(Vec::new()),
+            }))
+        }
+
+        /// 覆盖三个核心的 NPU 寄存器区域
+        pub fn npu() -> &'static Self {
+            Self::leak(3 * NPU_CORE_SIZE, true)
+        }
+
+        /// CRU 寄存器区域
+        pub fn cru() -> &'static Self {
+            Self::leak(RKNPU_CRU_REGISTERS_SIZE, false)
+        }
+
+        /// 直接设置寄存器值，不计入写入记录
+        pub fn set(&self, offset: usize, value: u32) {
+            self.regs[offset / 4].store(value, Ordering::SeqCst);
+        }
+
+        pub fn get(&self, offset: usize) -> u32 {
+            self.regs[offset / 4].load(Ordering::SeqCst)
+        }
+
+        /// 按顺序返回所有写入 `(偏移, 值)`
+        pub fn writes(&self) -> Vec<(usize, u32)> {
+            self.writes.lock().unwrap().clone()
+        }
+
+        /// 按顺序返回写入 `offset` 的值
+        pub fn writes_to(&self, offset: usize) -> Vec<u32> {
+            self.writes()
+                .into_iter()
+                .filter(|&(o, _)| o == offset)
+                .map(|(_, v)| v)
+                .collect()
+        }
+
+        /// 读取 `offset` 的次数
+        pub fn reads_of(&self, offset: usize) -> usize {
+            self.reads
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|&&o| o == offset)
+                .count()
+        }
+
+        pub fn clear_writes(&self) {
+            self.writes.lock().unwrap().clear();
+        }
+
+        /// 模拟卡死的寄存器：之后对 `offset` 的写入照常记录，但不改变寄存器值
+        pub fn stick(&self, offset: usize) {
+            self.stuck.lock().unwrap().push(offset);
+        }
+
+        /// 模拟读取与清除之间到达的中断：下一次读取 `status_offset` 处的 `int_status`
+        /// 返回后，`bits` 在该核心的 `int_status`/`int_raw_status` 中置位
+        pub fn arrive_after_status_read(&self, status_offset: usize, bits: u32) {
+            *self.arriving.lock().unwrap() = Some((status_offset, bits));
+        }
+
+        /// 模拟任务执行完成：下一次向 `op_en_offset` 处的 `pc_op_en` 写入非零值后，
+        /// `bits` 在该核心的 `int_status`/`int_raw_status` 中置位
+        pub fn arrive_on_start(&self, op_en_offset: usize, bits: u32) {
+            *self.on_start.lock().unwrap() = Some((op_en_offset, bits));
+        }
+
+        /// 之后对 `offset` 的读取依次返回 `values`，用完后保持最后一个值
+        pub fn script(&self, offset: usize, values: &[u32]) {
+            let mut scripted = self.scripted.lock().unwrap();
+            scripted.retain(|(o, _)| *o != offset);
+            scripted.push((offset, values.iter().copied().collect()));
+        }
+    }
+
+    impl Mmio for MockMmio {
+        fn read32(&self, offset: usize) -> u32 {
+            self.reads.lock().unwrap().push(offset);
+            if let Some((_, values)) = self
+                .scripted
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .find(|(o, _)| *o == offset)
+                && let Some(value) = values.pop_front()
+            {
+                self.set(offset, value);
+            }
+            let value = self.get(offset);
+            let mut arriving = self.arriving.lock().unwrap();
+            if let Some((status, bits)) = *arriving
+                && status == offset
+            {
+                *arriving = None;
+                let base = offset - npu_reg::INT_STATUS;
+                self.regs[offset / 4].fetch_or(bits, Ordering::SeqCst);
+                self.regs[(base + npu_reg::INT_RAW_STATUS) / 4].fetch_or(bits, Ordering::SeqCst);
+            }
+            value
+        }
+
+        fn write32(&self, offset: usize, value: u32) {
+            self.writes.lock().unwrap().push((offset, value));
+            if self.stuck.lock().unwrap().contains(&offset) {
+                return;
+            }
+            if self.npu && offset % NPU_CORE_SIZE == npu_reg::INT_CLEAR {
+                let base = offset - npu_reg::INT_CLEAR;
+                self.regs[(base + npu_reg::INT_STATUS) / 4].fetch_and(!value, Ordering::SeqCst);
+                self.regs[(base + npu_reg::INT_RAW_STATUS) / 4].fetch_and(!value, Ordering::SeqCst);
+                return;
+            }
+            self.set(offset, value);
+            let mut on_start = self.on_start.lock().unwrap();
+            if let Some((op_en, bits)) = *on_start
+                && op_en == offset
+                && value != 0
+            {
+                *on_start = None;
+                let base = offset - npu_reg::PC_OP_EN;
+                self.regs[(base + npu_reg::INT_STATUS) / 4].fetch_or(bits, Ordering::SeqCst);
+                self.regs[(base + npu_reg::INT_RAW_STATUS) / 4].fetch_or(bits, Ordering::SeqCst);
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use rockchip_pm::PD;
+
+use crate::types::RkNpuResult;
+
+/// 驱动记录的各 NPU 电源域开关状态
+///
+/// 来自驱动自身的开关记录，不读取 PMU 寄存器；经由其他途径改变的电源域不会反映在这里。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PowerState {
+    /// NPU 主电源域（NPU0 核心）
+    pub npu: bool,
+    /// NPU TOP 电源域，关闭时所有核心随之断电
+    pub nputop: bool,
+    /// NPU1 核心电源域
+    pub npu1: bool,
+    /// NPU2 核心电源域
+    pub npu2: bool,
+}
+
+impl PowerState {
+    /// 所有电源域均已关闭
+    pub const fn is_all_off(&self) -> bool {
+        !(self.npu || self.nputop || self.npu1 || self.npu2)
+    }
+}
+
+/// 由宿主系统实现的电源域控制
+///
+/// PMU 寄存器块通常与其他外设共用。宿主持有唯一的 `RockchipPM`（或等价的控制器），
+/// 在实现中完成互斥后以 `&'static` 引用交给驱动，多个 `RknpuDev` 实例和其他外设驱动
+/// 的电源操作都经由同一个控制器串行执行。未安装时驱动每次操作临时创建 `RockchipPM`。
+pub trait PowerControl: Sync {
+    /// 打开电源域
+    fn power_on(&self, domain: PD) -> RkNpuResult<()>;
+    /// 关闭电源域
+    fn power_off(&self, domain: PD) -> RkNpuResult<()>;
+}
@@ -6,14 +6,55 @@ use tock_registers::{
 register_structs! {
     pub RknpuCruRegisters {
         (0x0000 => _reserved0),
-        
+
+        /// NPLL 配置寄存器 PLL_CON120 (偏移 0x01E0)
+        ///
+        /// - Bit[9:0]: 反馈分频 m
+        (0x01E0 => pub npll_con0: ReadWrite<u32>),
+
+        /// NPLL 配置寄存器 PLL_CON121 (偏移 0x01E4)
+        ///
+        /// - Bit[5:0]: 预分频 p
+        /// - Bit[8:6]: 后分频 s
+        /// - Bit[13]: 下电
+        (0x01E4 => pub npll_con1: ReadWrite<u32>),
+
+        /// NPLL 小数分频寄存器 PLL_CON122 (偏移 0x01E8)
+        (0x01E8 => pub npll_con2: ReadWrite<u32>),
+
+        (0x01EC => _reserved1),
+
+        /// NPLL 状态寄存器 PLL_CON126 (偏移 0x01F8)
+        ///
+        /// - Bit[15]: 锁定状态
+        (0x01F8 => pub npll_con6: ReadOnly<u32>),
+
+        (0x01FC => _reserved2),
+
+        /// NPU 时钟选择寄存器 CLKSEL_CON73 (偏移 0x0424)
+        ///
+        /// - Bit[9:7]: 父时钟选择
+        /// - Bit[6:2]: 分频系数
+        ///
+        /// 同样采用高 16 位写使能掩码的写保护机制
+        (0x0424 => pub clksel_con_npu: ReadWrite<u32>),
+
+        (0x0428 => _reserved3),
+
+        /// NPU 时钟门控寄存器 CLKGATE_CON29 (偏移 0x0874)
+        ///
+        /// 写 1 关闭对应时钟，写 0 打开；高 16 位为写使能掩码
+        (0x0874 => pub clkgate_con_npu: ReadWrite<u32>),
+
+        (0x0878 => _reserved4),
+
         /// NPU 软复位控制寄存器 (偏移 0x0A00)
-        /// 
+        ///
         /// RK 芯片的写保护机制：
         /// - 高 16 位为写使能掩码 (write mask)
         /// - 低 16 位为实际的复位控制位
         /// - 写入时需要同时设置对应的掩码位
-        /// 
+        ///
         /// 复位位定义：
         /// - Bit 0: NPU0 AXI 复位
         /// - Bit 1: NPU0 AHB 复位
@@ -22,7 +63,7 @@ register_structs! {
         /// - Bit 4: NPU2 AXI 复位
         /// - Bit 5: NPU2 AHB 复位
         (0x0A00 => pub softrst_con_npu: ReadWrite<u32>),
-        
+
         (0x0A04 => @END),
     }
 }
@@ -66,16 +107,33 @@ register_structs! {
         /// PC DMA base address register
         (0x0034 => pub pc_dma_base_addr: ReadWrite<u32>),
 
-        (0x0038 => _reserved2),
+        /// PC DMA control register，置位后 PC 引擎按 `pc_dma_base_addr` 取任务
+        (0x0038 => pub pc_dma_ctrl: ReadWrite<u32>),
 
         /// PC task status register
         (0x003C => pub pc_task_status: ReadOnly<u32>),
 
         (0x0040 => _reserved3),
 
+        /// CNA 卷积缓冲区（CBUF）分配寄存器 CNA_CBUF_CON0 (偏移 0x1040)
+        ///
+        /// 由任务的 regcmd 写入，读到的是最近一次任务的配置
+        /// - Bit[3:0]: 分给输入特征数据的 bank 数
+        /// - Bit[7:4]: 分给权重的 bank 数
+        /// - Bit[12]: 特征数据复用（跨任务保留在 CBUF 中）
+        /// - Bit[13]: 权重复用
+        (0x1040 => pub cna_cbuf_con0: ReadOnly<u32>),
+
+        /// CNA 卷积缓冲区数据条目寄存器 CNA_CBUF_CON1 (偏移 0x1044)
+        ///
+        /// - Bit[13:0]: 每行特征数据占用的 CBUF 条目数
+        (0x1044 => pub cna_cbuf_con1: ReadOnly<u32>),
+
+        (0x1048 => _reserved4),
+
         (0x8010 => pub clr_all_rw_amount: WriteOnly<u32>),
 
-        (0x8014 => _reserved4),
+        (0x8014 => _reserved5),
 
         /// Data write amount register
         (0x8034 => pub dt_wr_amount: ReadOnly<u32>),
@@ -86,13 +144,67 @@ register_structs! {
         /// Weight read amount register
         (0x803C => pub wt_rd_amount: ReadOnly<u32>),
 
-        (0x8040 => _reserved5),
+        (0x8040 => _reserved6),
 
         /// Enable mask register (at offset 0xF008)
         (0xF008 => pub enable_mask: ReadWrite<u32>),
 
-        (0xF00C => _reserved6),
+        (0xF00C => _reserved7),
 
         (0xF010 => @END),
     }
 }
+
+/// `RknpuRegisters` 覆盖的寄存器空间大小（到 `@END` 为止）
+///
+/// 驱动访问的最高偏移在这一范围内，每个核心至少需要映射这么多字节
+pub const RKNPU_REGISTERS_SIZE: usize = core::mem::size_of::<RknpuRegisters>();
+
+/// `RknpuCruRegisters` 覆盖的寄存器空间大小（到 `@END` 为止）
+pub const RKNPU_CRU_REGISTERS_SIZE: usize = core::mem::size_of::<RknpuCruRegisters>();
+
+const _: () = assert!(RKNPU_REGISTERS_SIZE <= crate::configs::addresses::NPU_CORE_SIZE);
+const _: () = assert!(RKNPU_CRU_REGISTERS_SIZE <= crate::configs::addresses::CRU_SIZE);
+
+/// NPU 核心寄存器相对核心基址的偏移，由 `RknpuRegisters` 布局导出
+pub mod npu_reg {
+    use core::mem::offset_of;
+
+    use super::RknpuRegisters;
+
+    pub const VERSION: usize = offset_of!(RknpuRegisters, version);
+    pub const VERSION_NUM: usize = offset_of!(RknpuRegisters, version_num);
+    pub const PC_OP_EN: usize = offset_of!(RknpuRegisters, pc_op_en);
+    pub const PC_DATA_ADDR: usize = offset_of!(RknpuRegisters, pc_data_addr);
+    pub const PC_DATA_AMOUNT: usize = offset_of!(RknpuRegisters, pc_data_amount);
+    pub const INT_MASK: usize = offset_of!(RknpuRegisters, int_mask);
+    pub const INT_CLEAR: usize = offset_of!(RknpuRegisters, int_clear);
+    pub const INT_STATUS: usize = offset_of!(RknpuRegisters, int_status);
+    pub const INT_RAW_STATUS: usize = offset_of!(RknpuRegisters, int_raw_status);
+    pub const PC_TASK_CONTROL: usize = offset_of!(RknpuRegisters, pc_task_control);
+    pub const PC_DMA_BASE_ADDR: usize = offset_of!(RknpuRegisters, pc_dma_base_addr);
+    pub const PC_DMA_CTRL: usize = offset_of!(RknpuRegisters, pc_dma_ctrl);
+    pub const PC_TASK_STATUS: usize = offset_of!(RknpuRegisters, pc_task_status);
+    pub const CNA_CBUF_CON0: usize = offset_of!(RknpuRegisters, cna_cbuf_con0);
+    pub const CNA_CBUF_CON1: usize = offset_of!(RknpuRegisters, cna_cbuf_con1);
+    pub const CLR_ALL_RW_AMOUNT: usize = offset_of!(RknpuRegisters, clr_all_rw_amount);
+    pub const DT_WR_AMOUNT: usize = offset_of!(RknpuRegisters, dt_wr_amount);
+    pub const DT_RD_AMOUNT: usize = offset_of!(RknpuRegisters, dt_rd_amount);
+    pub const WT_RD_AMOUNT: usize = offset_of!(RknpuRegisters, wt_rd_amount);
+    pub const ENABLE_MASK: usize = offset_of!(RknpuRegisters, enable_mask);
+}
+
+/// CRU 寄存器相对 CRU 基址的偏移，由 `RknpuCruRegisters` 布局导出
+pub mod cru_reg {
+    use core::mem::offset_of;
+
+    use super::RknpuCruRegisters;
+
+    pub const NPLL_CON0: usize = offset_of!(RknpuCruRegisters, npll_con0);
+    pub const NPLL_CON1: usize = offset_of!(RknpuCruRegisters, npll_con1);
+    pub const NPLL_CON2: usize = offset_of!(RknpuCruRegisters, npll_con2);
+    pub const NPLL_CON6: usize = offset_of!(RknpuCruRegisters, npll_con6);
+    pub const CLKSEL_CON_NPU: usize = offset_of!(RknpuCruRegisters, clksel_con_npu);
+    pub const CLKGATE_CON_NPU: usize = offset_of!(RknpuCruRegisters, clkgate_con_npu);
+    pub const SOFTRST_CON_NPU: usize = offset_of!(RknpuCruRegisters, softrst_con_npu);
+}
@@ -1,25 +1,231 @@
-use core::ptr::{NonNull, addr_of};
+use alloc::{format, string::String};
+use core::{
+    ptr::{NonNull, addr_of},
+    sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering},
+};
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use memory_addr::{PhysAddr, VirtAddr, pa};
 use rk3588_rs::{
-    RKNPU_JOB_PINGPONG, RKNPU_PC_DATA_EXTRA_AMOUNT, RknpuAction, RknpuMemSync, RknpuSubmit,
-    RknpuTask,
+    RKNPU_PC_DATA_EXTRA_AMOUNT, RknpuAction, RknpuMemCreate, RknpuMemDestroy, RknpuMemSync,
+    RknpuSubmit, RknpuTask,
 };
 use rockchip_pm::{PD, RockchipPM};
-use tock_registers::interfaces::{Readable, Writeable};
 
 use crate::{
-    configs::{RK3588_NPU_VERSION, RknpuConfig},
-    registers::{RknpuCruRegisters, RknpuRegisters},
-    types::{NpuCore, RkBoard, RkNpuError, RkNpuResult, RknpuActionFlag},
+    configs::{
+        IntClearStrategy, NBUF_ALIGN, PcTaskControl, REGCMD_ENTRY_SIZE, RknpuConfig,
+        addresses::{CRU_SIZE, NPU_CORE_SIZE, NPU0_BASE, NPU1_BASE, NPU2_BASE, PMU1_SIZE},
+        cru_softrst::ResetKind,
+    },
+    events::{EventSink, NpuEvent},
+    fence::{FenceId, FenceProvider},
+    job::{JobId, JobTable, MAX_INFLIGHT},
+    memory::{BufferResolver, NpuAllocator},
+    mmio::{DirectMmio, Mmio},
+    power::{PowerControl, PowerState},
+    registers::{RKNPU_CRU_REGISTERS_SIZE, RKNPU_REGISTERS_SIZE, cru_reg, npu_reg},
+    sched::{CoreSelector, LeastLoaded, nice_from_action},
+    sram::SramAllocator,
+    stats::{CycleTracker, NpuStats, StatsCounters},
+    time::{Clock, Deadline},
+    types::{
+        BwPriority, CbufConfig, IntStatus, IrqEvent, NpuCore, RKNPU_MEM_SYNC_BY_HANDLE,
+        RKNPU_MEM_TRY_ALLOC_NBUF, RKNPU_NBUF_HANDLE, RKNPU_RESET_POWER_CYCLE,
+        RKNPU_TIMEOUT_INFINITE, RkBoard, RkNpuError, RkNpuResult, RknpuActionFlag, RknpuTaskView,
+        SubmitFlags, SubmitPriority, SyncDirection,
+    },
 };
 
 pub struct RknpuDev {
     config: RknpuConfig,
     core_base: usize,
     cru_base: usize,
-    pm_base: usize
+    pm_base: usize,
+    event_sink: Option<&'static dyn EventSink>,
+    fence_provider: Option<&'static dyn FenceProvider>,
+    buffer_resolver: Option<&'static dyn BufferResolver>,
+    /// 用户态地址转换，未安装时用户地址直接作为内核地址使用
+    allocator: Option<&'static (dyn NpuAllocator + Sync)>,
+    power: Option<&'static dyn PowerControl>,
+    jobs: JobTable,
+    core_selector: &'static dyn CoreSelector,
+    stats: StatsCounters,
+    cycles: CycleTracker,
+    self_test_on_init: bool,
+    allow_coherent_submit: bool,
+    verify_writes: bool,
+    /// 轮询等待时读取 `int_raw_status` 而非 `int_status`
+    poll_raw_status: bool,
+    /// `initialize` 成功后置位，`shutdown` 时清除
+    initialized: bool,
+    /// 提交到未上电的核心时自动上电
+    auto_power: bool,
+    /// 超时后 PC 引擎无法停止时执行总线复位
+    reset_on_timeout: bool,
+    /// 各核心是否有待处理的取消请求
+    abort_requested: [AtomicBool; 3],
+    /// 各核心最近一次写入 PC 引擎的任务数
+    queued_tasks: [AtomicU32; 3],
+    /// 被 `reserve_core` 预留的核心掩码
+    reserved_cores: AtomicU32,
+    /// 允许提交的核心掩码，`config.core_mask` 的子集
+    active_core_mask: u32,
+    /// 各核心是否有提交正在编程或等待，同一核心同时只允许一个提交
+    core_busy: [AtomicBool; 3],
+    /// 锁定为故障状态所需的连续硬件错误数，0 表示不锁定
+    fault_threshold: u32,
+    /// 连续的硬件错误数，成功提交后清零
+    consecutive_faults: AtomicU32,
+    /// 连续错误达到阈值后置位，拒绝后续提交
+    failed: AtomicBool,
+    /// 寄存器由模拟器提供
+    simulated: bool,
+    /// 已上电核心的掩码，位定义与 `core_mask` 相同
+    powered_cores: AtomicU32,
+    /// NPUTOP 电源域是否已上电
+    nputop_powered: AtomicBool,
+    /// 各核心当前中断统计窗口的起始时间（微秒）
+    irq_window_start: [AtomicU64; 3],
+    /// 各核心当前窗口内的中断次数
+    irq_window_count: [AtomicU32; 3],
+    sram: SramAllocator,
+    /// 整块 NBUF 是否已作为缓冲区句柄交出
+    nbuf_claimed: AtomicBool,
+    /// 通过 `SetProcNice` 设置的 nice 值
+    proc_nice: AtomicI32,
+    /// 带宽优先级寄存器区域的虚拟地址，0 表示未映射
+    bw_base: usize,
+    clock: Option<&'static dyn Clock>,
+    /// 替换直接访问的 NPU 核心寄存器区域，偏移相对 NPU0 基址
+    npu_mmio: Option<&'static dyn Mmio>,
+    /// 替换直接访问的 CRU 寄存器区域
+    cru_mmio: Option<&'static dyn Mmio>,
+    /// DMA 地址到内核虚拟地址的转换，提交路径上所有地址转换都经过它
+    dma_to_kernel: fn(PhysAddr) -> VirtAddr,
+}
+
+/// 挂起时保存的 NPU 寄存器状态
+///
+/// 只包含可读写的配置寄存器；`int_clear` 等只写/写清除寄存器、
+/// `int_status` 等只读寄存器以及 `pc_op_en` 这类脉冲寄存器不在保存范围内。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NpuSavedState {
+    /// 各核心的 `int_mask`
+    pub int_mask: [u32; 3],
+    /// 各核心的 `enable_mask`
+    pub enable_mask: [u32; 3],
+    /// 带宽优先级寄存器，`bw_enable` 关闭或区域未映射时为 `None`
+    pub bw_priority: Option<u32>,
+    /// 带宽仲裁模式寄存器
+    pub bw_mode: Option<u32>,
+    /// 期望带宽寄存器，超出 `bw_priority_length` 时为 `None`
+    pub bw_expect: Option<u32>,
+    /// 带宽统计时间窗寄存器，超出 `bw_priority_length` 时为 `None`
+    pub bw_tw: Option<u32>,
+}
+
+/// [`RknpuDev::reserve_core`] 返回的核心预留守卫，drop 时释放预留
+pub struct CoreReservation<'a> {
+    dev: &'a RknpuDev,
+    core: NpuCore,
+}
+
+impl CoreReservation<'_> {
+    /// 被预留的核心
+    pub fn core(&self) -> NpuCore {
+        self.core
+    }
+
+    /// 在预留的核心上提交，`submit.core_mask` 被覆盖为该核心
+    ///
+    /// 预留期间这是提交到该核心的唯一途径，其他显式指定该核心的提交返回 `Busy`。
+    pub fn submit(&self, submit: &mut RknpuSubmit) -> RkNpuResult<()> {
+        submit.core_mask = self.core.mask_bit();
+        self.dev
+            .submit_ioctl_held(submit, self.core.mask_bit())
+            .map(|_| ())
+    }
+}
+
+impl Drop for CoreReservation<'_> {
+    fn drop(&mut self) {
+        self.dev
+            .reserved_cores
+            .fetch_and(!self.core.mask_bit(), Ordering::AcqRel);
+        debug!("[RKNPU] {:?} released", self.core);
+    }
+}
+
+/// 提交期间对核心的占用，drop 时清除 `core_busy` 标志
+///
+/// 覆盖提交路径上的所有返回点，包括完成、超时、取消和编程失败
+struct CoreBusyGuard<'a> {
+    flag: &'a AtomicBool,
+}
+
+impl Drop for CoreBusyGuard<'_> {
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::Release);
+    }
+}
+
+/// 任务失败时采集的核心状态快照
+///
+/// 只包含读取时的寄存器值，不引用设备，可直接附在问题报告中。
+/// 驱动没有 IOMMU 支持，因此不包含 IOMMU 故障信息。
+#[derive(Debug, Clone, Copy)]
+pub struct HangReport {
+    pub core: NpuCore,
+    /// 核心上最早的在途任务
+    pub job: Option<JobId>,
+    pub int_status: IntStatus,
+    pub int_raw_status: IntStatus,
+    pub int_mask: u32,
+    pub pc_op_en: u32,
+    /// PC 引擎已完成的任务数
+    pub pc_task_status: u32,
+    /// 数据写出字节数
+    pub dt_wr_amount: u32,
+    /// 数据读入字节数
+    pub dt_rd_amount: u32,
+    /// 权重读入字节数
+    pub wt_rd_amount: u32,
+}
+
+/// 计算任务命令缓冲区的 DMA 范围 `(起始地址, 字节数)`
+///
+/// PC 引擎从 `regcmd_addr` 开始读取 `regcfg_amount` 条配置，
+/// 外加 `RKNPU_PC_DATA_EXTRA_AMOUNT` 条自身使用的配置，每条占 `REGCMD_ENTRY_SIZE` 字节。
+/// 长度溢出时返回 `None`
+pub fn command_buffer_range(task: &RknpuTask) -> Option<(u64, usize)> {
+    let regcmd_addr = task.regcmd_addr;
+    let entries = task.regcfg_amount.checked_add(RKNPU_PC_DATA_EXTRA_AMOUNT)?;
+    let len = (entries as usize).checked_mul(REGCMD_ENTRY_SIZE)?;
+    Some((regcmd_addr, len))
+}
+
+/// 提交涉及的任务描述符在内核虚拟地址空间中的范围 `(起始, 字节数)`
+///
+/// 只覆盖 `task_start..task_start + task_number`，用于任务描述符的缓存维护
+fn task_descriptor_range(task_base: *const RknpuTask, submit: &RknpuSubmit) -> (usize, usize) {
+    let start = task_base as usize + submit.task_start as usize * size_of::<RknpuTask>();
+    (start, submit.task_number as usize * size_of::<RknpuTask>())
+}
+
+/// 校验任务描述符的内核虚拟地址：必须非空且 8 字节对齐
+fn task_ptr(task_kva: VirtAddr) -> RkNpuResult<*const RknpuTask> {
+    if task_kva.as_usize() == 0 || !task_kva.as_usize().is_multiple_of(align_of::<u64>()) {
+        info!("[RKNPU] Invalid task_kva: {:?}", task_kva);
+        return Err(RkNpuError::InvalidTaskAddress);
+    }
+    Ok(task_kva.as_ptr() as *const RknpuTask)
+}
+
+/// 按值读取 `task_kva` 处的任务描述符
+fn read_task(task_kva: VirtAddr) -> RkNpuResult<RknpuTask> {
+    let task = task_ptr(task_kva)?;
+    Ok(unsafe { core::ptr::read_unaligned(task) })
 }
 
 #[inline(always)]
@@ -63,6 +269,50 @@ pub unsafe fn dcache_invalidate_range(start: usize, size: usize) {
     }
 }
 
+/// 缓存行大小
+const CACHE_LINE: usize = 64;
+
+/// 中断风暴判定窗口
+const IRQ_STORM_WINDOW_US: u64 = 1000;
+/// 一个窗口内允许的最大中断次数，超过即判定为中断风暴
+///
+/// 未安装时钟时无法计时，改为统计清除后仍然挂起的连续中断次数。
+const IRQ_STORM_THRESHOLD: u32 = 64;
+
+/// 按缓存行对齐后合并相邻或重叠的区间，按输入顺序对每个合并后的 `[start, end)` 调用 `f`
+fn for_each_coalesced(ranges: &[(usize, usize)], mut f: impl FnMut(usize, usize)) {
+    let mut current: Option<(usize, usize)> = None;
+    for &(addr, len) in ranges.iter().filter(|(_, len)| *len != 0) {
+        let start = addr & !(CACHE_LINE - 1);
+        let end = addr.saturating_add(len);
+        current = match current {
+            Some((cur_start, cur_end)) if start <= cur_end && cur_start <= end => {
+                Some((cur_start.min(start), cur_end.max(end)))
+            }
+            Some((cur_start, cur_end)) => {
+                f(cur_start, cur_end);
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((start, end)) = current {
+        f(start, end);
+    }
+}
+
+/// PC 模式提交由首个任务描述符计算出的寄存器值
+struct PcJobRegs {
+    /// 最后一个任务的下标
+    task_end: u32,
+    regcmd_addr: u64,
+    regcfg_amount: u32,
+    data_amount: u32,
+    int_mask: u32,
+    int_clear: u32,
+    task_control: u32,
+}
+
 /// NPU 主电源域
 pub const NPU: PD = PD(8);
 /// NPU TOP 电源域  
@@ -72,405 +322,4988 @@ pub const NPU1: PD = PD(10);
 /// NPU2 电源域
 pub const NPU2: PD = PD(11);
 
+/// 核心所在的电源域
+const fn core_domain(core: NpuCore) -> PD {
+    match core {
+        NpuCore::Npu0 => NPU,
+        NpuCore::Npu1 => NPU1,
+        NpuCore::Npu2 => NPU2,
+    }
+}
+
+/// 电源域对应的核心，NPUTOP 等非核心域返回 `None`
+fn core_for_domain(domain: PD) -> Option<NpuCore> {
+    [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2]
+        .into_iter()
+        .find(|&core| core_domain(core) == domain)
+}
+
 impl RknpuDev {
-    pub fn new(base: usize, cru_base: usize, pm_base: usize, board: RkBoard) -> Self {
+    /// 创建设备实例
+    ///
+    /// `dma_to_kernel` 把任务描述符、命令缓冲区等 DMA 地址转换为内核虚拟地址。
+    pub fn new(
+        base: usize,
+        cru_base: usize,
+        pm_base: usize,
+        board: RkBoard,
+        dma_to_kernel: fn(PhysAddr) -> VirtAddr,
+    ) -> Self {
+        let config = RknpuConfig::from_board(board);
         RknpuDev {
-            config: RknpuConfig::from_board(board),
+            config,
             core_base: base,
             cru_base,
             pm_base,
+            event_sink: None,
+            fence_provider: None,
+            buffer_resolver: None,
+            allocator: None,
+            power: None,
+            jobs: JobTable::new(),
+            core_selector: &LeastLoaded,
+            stats: StatsCounters::new(),
+            cycles: CycleTracker::new(),
+            self_test_on_init: false,
+            allow_coherent_submit: false,
+            verify_writes: false,
+            poll_raw_status: false,
+            initialized: false,
+            auto_power: false,
+            reset_on_timeout: false,
+            abort_requested: [const { AtomicBool::new(false) }; 3],
+            queued_tasks: [const { AtomicU32::new(0) }; 3],
+            reserved_cores: AtomicU32::new(0),
+            active_core_mask: config.core_mask,
+            core_busy: [const { AtomicBool::new(false) }; 3],
+            fault_threshold: 0,
+            consecutive_faults: AtomicU32::new(0),
+            failed: AtomicBool::new(false),
+            simulated: false,
+            powered_cores: AtomicU32::new(0),
+            nputop_powered: AtomicBool::new(false),
+            irq_window_start: [const { AtomicU64::new(0) }; 3],
+            irq_window_count: [const { AtomicU32::new(0) }; 3],
+            sram: SramAllocator::new(config.nbuf_phyaddr, config.nbuf_size, config.sram_alignment),
+            nbuf_claimed: AtomicBool::new(false),
+            proc_nice: AtomicI32::new(0),
+            bw_base: 0,
+            clock: None,
+            npu_mmio: None,
+            cru_mmio: None,
+            dma_to_kernel,
         }
     }
 
-    const fn core_regs(&self) -> &RknpuRegisters {
-        unsafe { &*(self.core_base as *const _) }
+    /// 创建设备实例并校验三个寄存器基地址互不重叠
+    ///
+    /// 按各区域的已知大小（NPU 为所有可用核心的寄存器窗口、`CRU_SIZE`、`PMU1_SIZE`）
+    /// 检查，任意两个区域重叠时返回 `InvalidInput`。常见于设备树复制粘贴
+    /// 导致两个基地址相同，此时寄存器写入会落到错误的硬件模块上。
+    pub fn try_new(
+        base: usize,
+        cru_base: usize,
+        pm_base: usize,
+        board: RkBoard,
+        dma_to_kernel: fn(PhysAddr) -> VirtAddr,
+    ) -> RkNpuResult<Self> {
+        let dev = Self::new(base, cru_base, pm_base, board, dma_to_kernel);
+        let regions = dev.register_regions();
+        for (i, &(name_a, base_a, len_a)) in regions.iter().enumerate() {
+            for &(name_b, base_b, len_b) in &regions[i + 1..] {
+                if base_a < base_b.saturating_add(len_b) && base_b < base_a.saturating_add(len_a) {
+                    error!(
+                        "[RKNPU] {} base 0x{:x} overlaps {} base 0x{:x}",
+                        name_a, base_a, name_b, base_b
+                    );
+                    return Err(RkNpuError::InvalidInput);
+                }
+            }
+        }
+        Ok(dev)
     }
 
-    const fn cru_regs(&self) -> &RknpuCruRegisters {
-        unsafe { &*(self.cru_base as *const _) }
+    /// 构造时传入的 NPU、CRU、PMU 寄存器区域 `(名称, 基地址, 大小)`
+    ///
+    /// NPU 区域覆盖所有可用核心的寄存器窗口
+    fn register_regions(&self) -> [(&'static str, usize, usize); 3] {
+        let npu_size = self
+            .available_cores()
+            .last()
+            .map_or(0, |core| self.config.core_base(0, core) + NPU_CORE_SIZE);
+        [
+            ("NPU", self.core_base, npu_size),
+            ("CRU", self.cru_base, CRU_SIZE),
+            ("PMU", self.pm_base, PMU1_SIZE),
+        ]
     }
 
-    pub fn initialize(&mut self) -> RkNpuResult<()> {
-        // Convert pm_base (usize) to NonNull<u8> expected by RockchipPM::new
-        let base_ptr = NonNull::new(self.pm_base as *mut u8)
-            .ok_or(RkNpuError::InvalidInput)?;
-        let mut pm = RockchipPM::new(base_ptr, rockchip_pm::RkBoard::Rk3588);
-        pm.power_domain_on(NPU1).unwrap();
-        pm.power_domain_on(NPU2).unwrap();
-        pm.power_domain_on(NPU).unwrap();
-        pm.power_domain_on(NPUTOP).unwrap();
+    /// 覆盖板型默认的 NBUF 物理地址和大小
+    ///
+    /// 用于 SRAM 保留区域与参考设计不同的载板。地址和大小必须按 `NBUF_ALIGN`
+    /// 对齐，且不能与构造时传入的 NPU、CRU、PMU 寄存器区域重叠（基地址为 0 的区域
+    /// 视为未配置，不参与检查）；`size` 为 0 表示禁用 NBUF。
+    pub fn with_nbuf(mut self, phyaddr: u64, size: u64) -> RkNpuResult<Self> {
+        if size == 0 {
+            self.config.nbuf_phyaddr = 0;
+            self.config.nbuf_size = 0;
+            self.sram = SramAllocator::new(0, 0, self.config.sram_alignment);
+            return Ok(self);
+        }
 
-        self.check_hardware_version()?;
-        Ok(())
-    }
+        if !phyaddr.is_multiple_of(NBUF_ALIGN) || !size.is_multiple_of(NBUF_ALIGN) {
+            error!(
+                "[RKNPU] NBUF 0x{:x}+0x{:x} is not {}-byte aligned",
+                phyaddr, size, NBUF_ALIGN
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
 
-    pub fn rknpu_action_ioctl(&self, action: &mut RknpuAction) -> RkNpuResult<()> {
-        match RknpuActionFlag::from(action.flags) {
-            RknpuActionFlag::GetHwVersion => {
-                action.value = self.core_regs().version.get();
-            }
-            RknpuActionFlag::ActReset => {
-                debug!("[RKNPU] Performing hardware reset");
-                // self.soft_reset()?;
+        let end = phyaddr.checked_add(size).ok_or(RkNpuError::InvalidInput)?;
+        for (name, base, len) in self.register_regions() {
+            if base == 0 {
+                continue;
             }
-            _ => {
-                error!("[RKNPU] Unsupported action flag: 0x{:x}", action.flags);
+            let (base, len) = (base as u64, len as u64);
+            if phyaddr < base.saturating_add(len) && base < end {
+                error!(
+                    "[RKNPU] NBUF 0x{:x}+0x{:x} overlaps {} region 0x{:x}+0x{:x}",
+                    phyaddr, size, name, base, len
+                );
                 return Err(RkNpuError::InvalidInput);
             }
         }
-        Ok(())
-    }
 
-    pub fn rknpu_submit_ioctl(
-        &self,
-        submit: &mut RknpuSubmit,
-        dma_to_kernel: fn(PhysAddr) -> VirtAddr,
-    ) -> RkNpuResult<()> {
-        debug!(
-            "[RKNPU] SUBMIT: task_obj_addr=0x{:x}, task_number={}, flags=0x{:x}, timeout={}, \
-             core_mask=0x{:x}",
-            submit.task_obj_addr,
-            submit.task_number,
-            submit.flags,
-            submit.timeout,
-            self.config.core_mask
-        );
+        self.config.nbuf_phyaddr = phyaddr;
+        self.config.nbuf_size = size;
+        self.sram = SramAllocator::new(phyaddr, size, self.config.sram_alignment);
+        Ok(self)
+    }
 
-        // 验证输入参数
-        if submit.task_number == 0 {
-            info!("[RKNPU] Invalid task_number: 0");
+    /// 校验调用者映射的核心寄存器窗口大小
+    ///
+    /// `size` 为从 `base` 开始映射的字节数，必须覆盖最后一个可用核心的
+    /// `RknpuRegisters`，否则访问高偏移寄存器会越过映射边界。
+    pub fn with_core_window_size(self, size: usize) -> RkNpuResult<Self> {
+        let (required, _) = self.required_mmio_sizes().ok_or(RkNpuError::InvalidInput)?;
+        if size < required {
+            error!(
+                "[RKNPU] Register window 0x{:x} too small, need at least 0x{:x}",
+                size, required
+            );
             return Err(RkNpuError::InvalidInput);
         }
+        Ok(self)
+    }
 
-        if submit.task_obj_addr == 0 {
-            info!("[RKNPU] Invalid task_obj_addr: 0");
-            return Err(RkNpuError::InvalidTaskAddress);
-        }
-
-        let task_base =
-            dma_to_kernel(pa!(submit.task_obj_addr as usize)).as_mut_ptr() as *const RknpuTask;
+    /// 驱动正常工作所需的最小映射大小 `(NPU 核心窗口, CRU)`
+    ///
+    /// NPU 窗口从 `base` 起覆盖到最后一个可用核心的 `RknpuRegisters` 末尾，
+    /// 比按 `NPU_CORE_SIZE` 整块映射更小；板型没有可用核心时返回 `None`
+    pub fn required_mmio_sizes(&self) -> Option<(usize, usize)> {
+        let last_core = self.available_cores().last()?;
+        let npu = self.config.core_base(0, last_core) + RKNPU_REGISTERS_SIZE;
+        Some((npu, RKNPU_CRU_REGISTERS_SIZE))
+    }
 
-        debug!(
-            "[RKNPU] Checking interrupt status before submission: 0x{:x}",
-            self.core_regs().int_status.get()
-        );
-        debug!(
-            "[RKNPU] Checking raw interrupt status: 0x{:x}",
-            self.core_regs().int_raw_status.get()
-        );
+    /// 设置 `initialize` 完成后是否对所有可用核心执行 [`self_test`](Self::self_test)
+    pub fn with_self_test(mut self, enabled: bool) -> Self {
+        self.self_test_on_init = enabled;
+        self
+    }
 
-        // 提交任务到硬件
-        self.job_commit_pc(task_base, submit)?;
+    /// 设置是否信任提交中的 `SubmitFlags::COHERENT` 标志
+    ///
+    /// 只有确认调用者会自行完成缓存维护（例如内核内的受信任推理服务）时才应开启；
+    /// 未开启时该标志被忽略，仍执行缓存刷新。
+    pub fn with_coherent_submit(mut self, allowed: bool) -> Self {
+        self.allow_coherent_submit = allowed;
+        self
+    }
 
-        // 等待任务完成
-        let timeout = if submit.timeout > 0 {
-            submit.timeout
-        } else {
-            5000 // 默认5秒超时
-        };
+    /// 设置 `config.bw_priority_addr` 映射后的虚拟地址
+    ///
+    /// 映射长度至少为 `config.bw_priority_length`；未设置时带宽优先级接口返回 `NotSupported`。
+    pub fn with_bw_priority_base(mut self, bw_base: usize) -> Self {
+        self.bw_base = bw_base;
+        self
+    }
 
-        // todo: get mem pool base addr
-        self.wait_job_done(timeout, task_base as usize - 0x1000usize)?;
+    /// 设置提交时是否回读校验关键寄存器的写入
+    ///
+    /// 开启后 `pc_data_amount`、`int_mask`、`pc_task_control` 写入后立即回读；
+    /// `int_mask`、`pc_task_control` 不一致时返回 `HardwareError`，`pc_data_amount` 只告警。
+    /// 用于排查时钟或电源配置错误导致总线丢弃写入的问题，每次提交多出三次寄存器读，默认关闭。
+    pub fn with_verify_writes(mut self, enabled: bool) -> Self {
+        self.verify_writes = enabled;
+        self
+    }
 
-        debug!("[RKNPU] Task submission completed successfully");
-        Ok(())
+    /// 设置轮询等待（`wait_int` / 任务完成等待）是否读取 `int_raw_status`
+    ///
+    /// 另有中断处理程序通过屏蔽 `int_mask` 来暂停中断时，屏蔽后的 `int_status` 不再反映事件，
+    /// 而原始状态不受 `int_mask` 影响，轮询方仍能观察到完成。注意 `int_clear` 同时清除
+    /// 原始状态：中断处理程序先清除时轮询方依然会错过事件，此时应以在途任务表
+    /// （[`current_job`](Self::current_job)）为准。原始状态包含被屏蔽的中间中断位，
+    /// 只有与等待掩码相交的位才会被视为命中。
+    pub fn with_raw_status_polling(mut self, enabled: bool) -> Self {
+        self.poll_raw_status = enabled;
+        self
     }
 
-    pub fn rknpu_mem_sync_ioctl(&self, _mem_sync: &RknpuMemSync) -> RkNpuResult<()> {
-        // Handle RKNPU_MEM_SYNC ioctl
-        Ok(())
+    /// 标记寄存器由模拟器而非真实硬件提供，只影响上报给用户态的驱动描述
+    pub fn with_simulated(mut self, simulated: bool) -> Self {
+        self.simulated = simulated;
+        self
     }
 
-    fn check_hardware_version(&self) -> RkNpuResult<()> {
-        let version = self.core_regs().version.get();
-        if version == RK3588_NPU_VERSION {
-            Ok(())
-        } else {
-            Err(RkNpuError::UnsupportedVersion)
-        }
+    /// `DRM_IOCTL_VERSION` 上报的驱动描述，如 `"Rockchip RK3588 NPU"`
+    pub fn drm_desc(&self) -> String {
+        let suffix = if self.simulated { " Simulated" } else { "" };
+        format!("Rockchip {} NPU{}", self.config.board.name(), suffix)
     }
 
-    /// PC 模式硬件任务提交
-    fn job_commit_pc(
-        &self,
-        task_base: *const RknpuTask,
-        submit: &mut RknpuSubmit,
-    ) -> RkNpuResult<()> {
-        if task_base.is_null() {
-            return Err(RkNpuError::InvalidTaskAddress);
-        }
+    /// 连续 `threshold` 次提交以 `HardwareError` 失败后拒绝后续提交，0（默认）表示不限制
+    pub fn with_fault_threshold(mut self, threshold: u32) -> Self {
+        self.fault_threshold = threshold;
+        self
+    }
 
-        debug!(
-            "[RKNPU] Committing PC job: task_base={:x}, task_start={}, task_number={}, \
-             flags=0x{:x}",
-            task_base as usize, submit.task_start, submit.task_number, submit.flags
-        );
+    /// 是否因连续硬件错误被锁定为故障状态
+    pub fn is_failed(&self) -> bool {
+        self.failed.load(Ordering::Acquire)
+    }
 
-        unsafe {
-            let task_end = submit.task_start + submit.task_number - 1;
-            let first_task = task_base.add(submit.task_start as usize);
-            let last_task = task_base.add(task_end as usize);
+    /// 任务超时且 PC 引擎无法停止时执行总线复位，默认关闭
+    pub fn with_reset_on_timeout(mut self, enabled: bool) -> Self {
+        self.reset_on_timeout = enabled;
+        self
+    }
 
-            // todo: get task mem size
-            dcache_flush_range(task_base as usize, 1024);
-            let reg_addr_kva = core::ptr::read_unaligned(addr_of!((*first_task).regcmd_addr))
-                + 0xffff_0000_0000_0000;
+    /// 覆盖板型默认的完成中断清除方式（`config.int_clear_strategy`）
+    pub fn with_int_clear_strategy(mut self, strategy: IntClearStrategy) -> Self {
+        self.config.int_clear_strategy = strategy;
+        self
+    }
 
-            dcache_flush_range(reg_addr_kva as usize, 8 * 1024 * 1024);
+    /// 提交到未上电的核心时自动打开其电源域，默认关闭（返回 `DomainNotFound`）
+    pub fn with_auto_power(mut self, enabled: bool) -> Self {
+        self.auto_power = enabled;
+        self
+    }
 
-            debug!(
-                "[RKNPU] First task addr 0x{:x}, int_mask {}, regcmd_addr 0x{:x}",
-                first_task as usize,
-                core::ptr::read_unaligned(addr_of!((*first_task).int_mask)),
-                core::ptr::read_unaligned(addr_of!((*first_task).regcmd_addr))
-            );
+    /// 安装结构化事件接收者，未安装时不产生任何事件
+    pub fn set_event_sink(&mut self, sink: &'static dyn EventSink) {
+        self.event_sink = Some(sink);
+    }
 
-            let tasks = &mut *(first_task as *mut RknpuTask);
-            debug!("{:#?}", tasks);
+    fn emit(&self, event: NpuEvent) {
+        if let Some(sink) = self.event_sink {
+            sink.on_event(event);
+        }
+    }
 
-            // 读取第一个任务的配置（使用 read_unaligned 因为是 packed struct）
-            let first_regcmd_addr = core::ptr::read_unaligned(addr_of!((*first_task).regcmd_addr));
-            let first_regcfg_amount =
-                core::ptr::read_unaligned(addr_of!((*first_task).regcfg_amount));
-            let first_int_clear = core::ptr::read_unaligned(addr_of!((*first_task).int_clear));
+    /// 安装 fence 原语，之后带 `RKNPU_JOB_FENCE_OUT` 的提交会产生输出 fence
+    pub fn set_fence_provider(&mut self, provider: &'static dyn FenceProvider) {
+        self.fence_provider = Some(provider);
+    }
 
-            // 读取最后一个任务的中断掩码
-            let last_int_mask = core::ptr::read_unaligned(addr_of!((*last_task).int_mask));
+    /// 安装缓冲区查询，之后提交时校验任务范围不超出任务缓冲区
+    pub fn set_buffer_resolver(&mut self, resolver: &'static dyn BufferResolver) {
+        self.buffer_resolver = Some(resolver);
+    }
 
-            let pc_data_amount_scale = self.config.pc_data_amount_scale;
-            let task_pp_en = if submit.flags & RKNPU_JOB_PINGPONG != 0 {
-                1
-            } else {
-                0
-            };
-            let pc_task_number_bits = self.config.pc_task_number_bits;
+    /// 安装用户态地址转换，之后写回用户缓冲区前先确认整个范围已映射
+    pub fn set_allocator(&mut self, allocator: &'static (dyn NpuAllocator + Sync)) {
+        self.allocator = Some(allocator);
+    }
 
-            debug!(
-                "[RKNPU] Committing PC job: task_start={}, task_number={}",
-                submit.task_start, submit.task_number
-            );
-            debug!(
-                "[RKNPU] First task regcmd_addr=0x{:x}, regcfg_amount={}",
-                first_regcmd_addr, first_regcfg_amount
+    /// 把用户态缓冲区 `[user_addr, user_addr + len)` 转换为可写的内核指针
+    ///
+    /// 安装了 [`NpuAllocator`] 时首尾两端都必须可转换且在内核中连续，否则返回
+    /// `InvalidInput`；未安装时用户地址与内核地址相同，只检查非空和范围不溢出。
+    pub(crate) fn user_buffer(&self, user_addr: usize, len: usize) -> RkNpuResult<*mut u8> {
+        let last = len
+            .checked_sub(1)
+            .and_then(|tail| user_addr.checked_add(tail))
+            .ok_or(RkNpuError::InvalidInput)?;
+        if user_addr == 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        let Some(allocator) = self.allocator else {
+            return Ok(user_addr as *mut u8);
+        };
+        let start = allocator.user_to_kernel_addr(user_addr)?;
+        let end = allocator.user_to_kernel_addr(last)?;
+        if end.as_usize().wrapping_sub(start.as_usize()) != len - 1 {
+            error!(
+                "[RKNPU] User buffer 0x{:x}+{} is not contiguous in kernel space",
+                user_addr, len
             );
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(start.as_mut_ptr())
+    }
 
-            // 1. 切换到 slave 模式
-            self.core_regs().pc_data_addr.set(0x1);
-
-            // 2. 写 regcmd 地址（只使用低32位）
-            self.core_regs().pc_data_addr.set(first_regcmd_addr as u32);
-
-            // 3. 计算并写数据量
-            let data_amount =
-                (first_regcfg_amount + RKNPU_PC_DATA_EXTRA_AMOUNT + pc_data_amount_scale - 1)
-                    / pc_data_amount_scale
-                    - 1;
-            debug!("[RKNPU] Data amount: {}", data_amount);
-            self.core_regs().pc_data_amount.set(data_amount);
-
-            // 4. 写中断掩码
-            self.core_regs().int_mask.set(last_int_mask);
+    /// 安装共享的电源域控制器，之后所有电源操作都经由它执行
+    pub fn set_power_control(&mut self, power: &'static dyn PowerControl) {
+        self.power = Some(power);
+    }
 
-            // 5. 清除中断
-            self.core_regs().int_clear.set(first_int_clear);
+    /// 安装单调时钟，之后超时和延时按实际经过的时间计算
+    pub fn set_clock(&mut self, clock: &'static dyn Clock) {
+        self.clock = Some(clock);
+    }
 
-            // 6. 写任务控制
-            let pc_task_control = ((0x6 | task_pp_en) << pc_task_number_bits) | submit.task_number;
-            debug!("[RKNPU] PC task control: 0x{:x}", pc_task_control);
-            self.core_regs().pc_task_control.set(pc_task_control);
+    /// 通过自定义传输访问寄存器，例如虚拟化客户机中经 hypercall 转发
+    ///
+    /// `npu` 的偏移相对 NPU0 寄存器基址，覆盖所有核心；`cru` 的偏移相对 CRU 基址。
+    /// 电源域仍由 `pm_base` 或 [`PowerControl`] 控制。
+    pub fn set_mmio(&mut self, npu: &'static dyn Mmio, cru: &'static dyn Mmio) {
+        self.npu_mmio = Some(npu);
+        self.cru_mmio = Some(cru);
+    }
 
-            // 7. 提交任务
-            self.core_regs().pc_op_en.set(0x1);
-            self.core_regs().pc_op_en.set(0x0);
+    /// 替换默认的核心选择策略（[`LeastLoaded`]）
+    pub fn set_core_selector(&mut self, selector: &'static dyn CoreSelector) {
+        self.core_selector = selector;
+    }
 
-            debug!("[RKNPU] Task submitted to hardware");
+    /// 限制驱动使用的核心，`mask` 必须是板型 `core_mask` 的非空子集
+    ///
+    /// 用于在现场屏蔽出现故障的核心而无需重新编译：被屏蔽的核心不再参与自动选择，
+    /// 显式指定它的提交返回 `CoreUnavailable`。已在核心上执行的任务不受影响。
+    pub fn set_active_core_mask(&mut self, mask: u32) -> RkNpuResult<()> {
+        if mask == 0 || mask & !self.config.core_mask != 0 {
+            info!(
+                "[RKNPU] Invalid active core mask 0x{:x}, available 0x{:x}",
+                mask, self.config.core_mask
+            );
+            return Err(RkNpuError::InvalidInput);
         }
-
+        info!("[RKNPU] Active core mask 0x{:x}", mask);
+        self.active_core_mask = mask;
         Ok(())
     }
 
-    /// 等待任务完成
-    fn wait_job_done(&self, timeout_ms: u32, pool_start: usize) -> RkNpuResult<()> {
-        debug!(
-            "[RKNPU] Waiting for job completion (timeout: {}ms)",
-            timeout_ms
-        );
-
-        // 简单的轮询实现，每次检查间隔约10微秒
-        let max_iterations = (timeout_ms as usize) * 100; // 10us * 100 = 1ms
-
-        for i in 0..max_iterations {
-            let int_status = self.core_regs().int_status.get();
+    /// 当前允许提交的核心掩码
+    pub fn active_core_mask(&self) -> u32 {
+        self.active_core_mask
+    }
 
-            // 检查中断状态（任何非零值表示有中断）
-            if int_status == 0x100 || int_status == 0x200 {
-                debug!(
-                    "[RKNPU] Job completed after {} iterations, int_status=0x{:x}",
-                    i, int_status
+    /// 根据提交的 `core_mask` 选择执行核心
+    ///
+    /// `core_mask` 为 0 表示任意核心，候选为启用的核心（见
+    /// [`set_active_core_mask`](Self::set_active_core_mask)）中未被
+    /// [`reserve_core`](Self::reserve_core) 预留的核心；否则候选必须是启用核心的子集，
+    /// 且不能包含 `held` 以外被预留的核心，否则返回 `Busy`。
+    /// `held` 为调用者通过 [`CoreReservation`] 持有的核心掩码。
+    fn select_core(
+        &self,
+        core_mask: u32,
+        priority: SubmitPriority,
+        held: u32,
+    ) -> RkNpuResult<NpuCore> {
+        let reserved = self.reserved_cores.load(Ordering::Acquire);
+        let candidates = if core_mask == 0 {
+            let candidates = self.active_core_mask & !reserved;
+            if candidates == 0 {
+                info!("[RKNPU] All active cores reserved");
+                return Err(RkNpuError::CoreUnavailable);
+            }
+            candidates
+        } else {
+            if core_mask & reserved & !held != 0 {
+                info!(
+                    "[RKNPU] core_mask 0x{:x} includes reserved cores 0x{:x}",
+                    core_mask, reserved
                 );
+                return Err(RkNpuError::Busy);
+            }
+            core_mask
+        };
+        if candidates & !self.config.core_mask != 0 {
+            info!(
+                "[RKNPU] Invalid core_mask: 0x{:x}, available 0x{:x}",
+                core_mask, self.config.core_mask
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        if candidates & !self.active_core_mask != 0 {
+            info!(
+                "[RKNPU] core_mask 0x{:x} includes disabled cores, active 0x{:x}",
+                core_mask, self.active_core_mask
+            );
+            return Err(RkNpuError::CoreUnavailable);
+        }
 
-                debug!("dcache {:#x}", pool_start);
-                unsafe {
-                    dcache_invalidate_range(pool_start, 8 * 1024 * 1024);
-                }
+        let pending =
+            [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2].map(|core| self.jobs.pending_on(core));
+        self.core_selector
+            .select_core_for(candidates, pending, priority)
+            .filter(|core| candidates & core.mask_bit() != 0)
+            .ok_or(RkNpuError::CoreUnavailable)
+    }
 
-                // 清除中断
-                self.core_regs().int_clear.set(int_status);
+    /// 预留核心，守卫存活期间“任意核心”的提交不会被分配到该核心
+    ///
+    /// 预留者通过 [`CoreReservation::submit`] 独占使用它，其他显式指定该核心的
+    /// 提交返回 `Busy`。
+    /// 核心不可用返回 `DomainNotFound`，已被预留返回 `Busy`。
+    pub fn reserve_core(&self, core: NpuCore) -> RkNpuResult<CoreReservation<'_>> {
+        if !self.config.is_available(core) {
+            return Err(RkNpuError::DomainNotFound);
+        }
+        let prev = self
+            .reserved_cores
+            .fetch_or(core.mask_bit(), Ordering::AcqRel);
+        if prev & core.mask_bit() != 0 {
+            return Err(RkNpuError::Busy);
+        }
+        debug!("[RKNPU] {:?} reserved", core);
+        Ok(CoreReservation { dev: self, core })
+    }
 
-                return Ok(());
-            }
+    /// 获取指定核心上正在执行的任务
+    pub fn current_job(&self, core: NpuCore) -> Option<JobId> {
+        self.jobs.current(core)
+    }
 
-            // 简单延迟（实际延迟取决于系统）
-            for _ in 0..100 {
-                core::hint::spin_loop();
-            }
+    /// 非阻塞地判断指定核心上是否有任务正在执行
+    ///
+    /// 核心上有在途任务且 `int_status` 尚未出现完成位时返回 `true`；
+    /// 不在 `core_mask` 中的核心始终返回 `false`。
+    pub fn is_busy(&self, core: NpuCore) -> bool {
+        if self.core_slot(core).is_err() || self.jobs.pending_on(core) == 0 {
+            return false;
         }
+        self.read_core(core, npu_reg::INT_STATUS) & self.config.int_done_mask == 0
+    }
 
-        info!("[RKNPU] Job timeout after {}ms, status=0x{:x}", timeout_ms, self.core_regs().int_status.get());
-        Err(RkNpuError::TaskTimeout)
+    /// 从 NBUF SRAM 分配 `size` 字节，返回物理地址
+    ///
+    /// 分配按 `config.sram_alignment` 对齐；没有 NBUF 的板型返回 `OutOfMemory`
+    pub fn alloc_sram(&self, size: u64) -> RkNpuResult<u64> {
+        self.sram.alloc(size)
     }
 
-    pub fn handle_irq(&self, _core: NpuCore) -> RkNpuResult<u32> {
-        let int_status = self.core_regs().int_status.get();
-        if int_status != 0 {
-            // 清除中断
-            self.core_regs().int_clear.set(int_status);
-            Ok(int_status)
-        } else {
-            Err(RkNpuError::NoInterrupt)
-        }
+    /// 释放由 [`alloc_sram`](Self::alloc_sram) 分配的 SRAM
+    pub fn free_sram(&self, addr: u64, size: u64) -> RkNpuResult<()> {
+        self.sram.free_range(addr, size)
     }
 
-    /// 微秒级延迟
-    fn delay_us(&self, us: u32) {
-        // 简单的忙等待实现
-        for _ in 0..(us * 100) {
-            core::hint::spin_loop();
+    /// 处理带 `RKNPU_MEM_TRY_ALLOC_NBUF` 的 `MEM_CREATE`
+    ///
+    /// 把整块 NBUF SRAM 作为缓冲区交给调用者（例如存放模型权重），不分配 DRAM；
+    /// 句柄固定为 `RKNPU_NBUF_HANDLE`，`dma_addr` 为 NBUF 物理地址，由宿主负责 mmap。
+    /// NBUF 同一时间只能被一个句柄或 [`alloc_sram`](Self::alloc_sram) 的分配占用，
+    /// 已被占用时返回 `Busy`；板型没有 NBUF 或请求大小超出时返回 `InvalidInput`。
+    pub fn rknpu_mem_create_ioctl(&self, mem: &mut RknpuMemCreate) -> RkNpuResult<()> {
+        if mem.flags & RKNPU_MEM_TRY_ALLOC_NBUF == 0 {
+            return Err(RkNpuError::NotSupported);
+        }
+        let total = self.sram.total();
+        if total == 0 || mem.size > total {
+            error!(
+                "[RKNPU] NBUF buffer of 0x{:x} bytes unavailable (NBUF size 0x{:x})",
+                mem.size, total
+            );
+            return Err(RkNpuError::InvalidInput);
         }
+        if self.nbuf_claimed.swap(true, Ordering::AcqRel) {
+            return Err(RkNpuError::Busy);
+        }
+        let phys = match self.sram.alloc(total) {
+            Ok(phys) => phys,
+            Err(_) => {
+                self.nbuf_claimed.store(false, Ordering::Release);
+                return Err(RkNpuError::Busy);
+            }
+        };
+
+        mem.handle = RKNPU_NBUF_HANDLE;
+        mem.size = total;
+        mem.sram_size = total;
+        mem.dma_addr = phys;
+        mem.obj_addr = 0;
+        debug!(
+            "[RKNPU] NBUF handed out as buffer: 0x{:x}+0x{:x}",
+            phys, total
+        );
+        Ok(())
     }
 
-    /// 清除中断状态
-    fn clear_interrupts(&self) -> RkNpuResult<()> {
-        use crate::configs::INT_CLEAR_VALUE;
-        self.core_regs().int_clear.set(INT_CLEAR_VALUE);
-        info!("[RKNPU] Interrupts cleared");
+    /// 处理 NBUF 句柄的 `MEM_DESTROY`，归还整块 NBUF
+    pub fn rknpu_mem_destroy_ioctl(&self, mem: &RknpuMemDestroy) -> RkNpuResult<()> {
+        if mem.handle != RKNPU_NBUF_HANDLE || !self.nbuf_claimed.load(Ordering::Acquire) {
+            return Err(RkNpuError::InvalidInput);
+        }
+        self.sram
+            .free_range(self.config.nbuf_phyaddr, self.sram.total())?;
+        self.nbuf_claimed.store(false, Ordering::Release);
         Ok(())
     }
 
-    /// 禁用所有使能位
-    fn disable_enables(&self) -> RkNpuResult<()> {
-        // 禁用 PC 操作
-        self.core_regs().pc_op_en.set(0);
-        // 清除使能掩码
-        self.core_regs().enable_mask.set(0);
-        info!("[RKNPU] All enables disabled");
+    /// 对多个分散的缓冲区批量执行缓存维护
+    ///
+    /// `ranges` 为 `(虚拟地址, 长度)` 列表。按输入顺序合并相邻区间，
+    /// 所有区间处理完后只执行一次 `dsb ish`，适合含大量小张量的模型。
+    pub fn mem_sync_scatter(
+        &self,
+        ranges: &[(usize, usize)],
+        dir: SyncDirection,
+    ) -> RkNpuResult<()> {
+        for_each_coalesced(ranges, |start, end| {
+            for addr in (start..end).step_by(CACHE_LINE) {
+                unsafe {
+                    match dir {
+                        SyncDirection::ToDevice => core::arch::asm!(
+                            "dc cvac, {0}",
+                            in(reg) addr,
+                            options(nostack, preserves_flags)
+                        ),
+                        SyncDirection::FromDevice => core::arch::asm!(
+                            "dc ivac, {0}",
+                            in(reg) addr,
+                            options(nostack, preserves_flags)
+                        ),
+                        SyncDirection::Bidirectional => core::arch::asm!(
+                            "dc civac, {0}",
+                            in(reg) addr,
+                            options(nostack, preserves_flags)
+                        ),
+                    }
+                }
+            }
+        });
+        unsafe {
+            core::arch::asm!("dsb ish", "isb", options(nostack, preserves_flags));
+        }
         Ok(())
     }
 
-    /// 执行 AXI 总线复位
+    /// 剩余可用的 SRAM 字节数
     ///
-    /// AXI 复位会重置 NPU 的 AXI 总线接口
-    fn reset_axi(&self) -> RkNpuResult<()> {
-        use crate::configs::cru_softrst::*;
+    /// 已扣除对齐造成的浪费，但空闲区域可能不连续，
+    /// 判断单个缓冲区能否放下应使用 [`largest_free_sram_block`](Self::largest_free_sram_block)
+    pub fn free_sram_size(&self) -> u64 {
+        self.sram.free()
+    }
 
-        info!("[RKNPU] Performing AXI reset");
+    /// 最大的连续空闲 SRAM 字节数
+    pub fn largest_free_sram_block(&self) -> u64 {
+        self.sram.largest_free()
+    }
 
-        // 只复位 NPU0 核心（当前只使用单核）
-        let reset_bit = NPU0_AXI_SRST;
+    /// 指定核心寄存器块的物理基地址（来自 `addresses` 中的 NPU0/1/2 定义）
+    ///
+    /// 用于与设备树核对内存映射或建立 MMIO 映射；核心不在 `core_mask` 中时返回 `None`
+    pub fn core_phys_base(&self, core: NpuCore) -> Option<usize> {
+        let base = match core {
+            NpuCore::Npu0 => NPU0_BASE,
+            NpuCore::Npu1 => NPU1_BASE,
+            NpuCore::Npu2 => NPU2_BASE,
+        };
+        self.config.is_available(core).then_some(base)
+    }
 
-        // RK 芯片的写保护机制：高 16 位为写使能掩码
-        // 步骤 1: 置位 - 触发复位
-        let set_value = (1 << (reset_bit + WRITE_MASK_SHIFT)) | (1 << reset_bit);
-        self.cru_regs().softrst_con_npu.set(set_value);
+    /// 最近一次 `SetProcNice` 设置的 nice 值，未设置时为 0
+    pub fn proc_nice(&self) -> i32 {
+        self.proc_nice.load(Ordering::Relaxed)
+    }
 
-        // 步骤 2: 等待复位生效（至少 10us）
-        self.delay_us(10);
+    /// 当前设备使用的硬件配置（含 `with_*` 的覆盖），只读
+    pub fn config(&self) -> &RknpuConfig {
+        &self.config
+    }
 
-        // 步骤 3: 清零 - 释放复位
-        let clear_value = (1 << (reset_bit + WRITE_MASK_SHIFT)) | (0 << reset_bit);
-        self.cru_regs().softrst_con_npu.set(clear_value);
+    /// 可用核心数
+    pub fn num_cores(&self) -> usize {
+        self.config.core_mask.count_ones() as usize
+    }
 
-        // 步骤 4: 等待稳定
-        self.delay_us(5);
+    /// 可用核心掩码
+    pub fn core_mask(&self) -> u32 {
+        self.config.core_mask
+    }
 
-        info!("[RKNPU] AXI reset completed");
+    /// 获取驱动统计信息
+    pub fn stats(&self) -> NpuStats {
+        self.stats.snapshot()
+    }
+
+    /// 已提交到硬件但尚未完成的任务数，可用于调度器做准入控制
+    pub fn pending_jobs(&self) -> usize {
+        self.jobs.pending()
+    }
+
+    /// 指定核心上已提交到硬件但尚未完成的任务数
+    pub fn pending_jobs_on(&self, core: NpuCore) -> usize {
+        self.jobs.pending_on(core)
+    }
+
+    /// 导出任务的完成 fence
+    ///
+    /// 任务仍在执行时返回其 fence（没有则新建并挂上），任务完成时由驱动触发；
+    /// 任务已经完成时返回一个已触发的 fence。未安装 fence 原语时返回 `NotSupported`。
+    pub fn export_fence(&self, job: JobId) -> RkNpuResult<FenceId> {
+        let provider = self.fence_provider.ok_or(RkNpuError::NotSupported)?;
+        match self.jobs.lookup(job) {
+            Some(Some(fence)) => return Ok(fence),
+            Some(None) => {}
+            None => {
+                let fence = provider.create_fence()?;
+                provider.signal_fence(fence, Ok(()));
+                return Ok(fence);
+            }
+        }
+
+        let fence = provider.create_fence()?;
+        match self.jobs.attach_fence(job, fence) {
+            Ok(()) => Ok(fence),
+            Err(Some(existing)) => {
+                provider.signal_fence(fence, Ok(()));
+                Ok(existing)
+            }
+            Err(None) => {
+                provider.signal_fence(fence, Ok(()));
+                Ok(fence)
+            }
+        }
+    }
+
+    /// 结束核心上的在途任务并触发其 fence
+    fn complete_job(&self, core: NpuCore, result: RkNpuResult<()>) {
+        if let Some((job, Some(fence))) = self.jobs.finish(core) {
+            debug!("[RKNPU] Signaling fence {:?} of job {:?}", fence, job);
+            if let Some(provider) = self.fence_provider {
+                provider.signal_fence(fence, result);
+            }
+        }
+    }
+
+    /// 读核心寄存器，`offset` 相对核心寄存器基址
+    #[inline(always)]
+    fn read_core(&self, core: NpuCore, offset: usize) -> u32 {
+        match self.npu_mmio {
+            Some(mmio) => mmio.read32(self.config.core_base(0, core) + offset),
+            None => DirectMmio::new(self.config.core_base(self.core_base, core)).read32(offset),
+        }
+    }
+
+    /// 写核心寄存器，`offset` 相对核心寄存器基址
+    #[inline(always)]
+    fn write_core(&self, core: NpuCore, offset: usize, value: u32) {
+        match self.npu_mmio {
+            Some(mmio) => mmio.write32(self.config.core_base(0, core) + offset, value),
+            None => {
+                DirectMmio::new(self.config.core_base(self.core_base, core)).write32(offset, value)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn read_cru(&self, offset: usize) -> u32 {
+        match self.cru_mmio {
+            Some(mmio) => mmio.read32(offset),
+            None => DirectMmio::new(self.cru_base).read32(offset),
+        }
+    }
+
+    #[inline(always)]
+    fn write_cru(&self, offset: usize, value: u32) {
+        match self.cru_mmio {
+            Some(mmio) => mmio.write32(offset, value),
+            None => DirectMmio::new(self.cru_base).write32(offset, value),
+        }
+    }
+
+    /// 板型可用的核心
+    fn available_cores(&self) -> impl Iterator<Item = NpuCore> + '_ {
+        [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2]
+            .into_iter()
+            .filter(|&core| self.config.is_available(core))
+    }
+
+    /// 校验核心属于板型可用核心，返回其在按核心索引的数组中的下标
+    ///
+    /// 单核板型上访问 NPU1/NPU2 会返回 `InvalidInput`，而不是访问未映射的寄存器
+    fn core_slot(&self, core: NpuCore) -> RkNpuResult<usize> {
+        if self.config.is_available(core) {
+            Ok(core.index())
+        } else {
+            error!(
+                "[RKNPU] {:?} is not available, core_mask=0x{:x}",
+                core, self.config.core_mask
+            );
+            Err(RkNpuError::InvalidInput)
+        }
+    }
+
+    fn power_manager(&self) -> RkNpuResult<RockchipPM> {
+        // Convert pm_base (usize) to NonNull<u8> expected by RockchipPM::new
+        let base_ptr = NonNull::new(self.pm_base as *mut u8).ok_or(RkNpuError::InvalidInput)?;
+        Ok(RockchipPM::new(base_ptr, rockchip_pm::RkBoard::Rk3588))
+    }
+
+    /// 按顺序打开或关闭电源域
+    ///
+    /// 安装了 [`PowerControl`] 时经由共享控制器执行，否则临时创建 `RockchipPM`
+    fn set_power_domains(&self, domains: &[PD], on: bool) -> RkNpuResult<()> {
+        if let Some(power) = self.power {
+            for &domain in domains {
+                if on {
+                    power.power_on(domain)?;
+                } else {
+                    power.power_off(domain)?;
+                }
+                self.track_power(domain, on);
+            }
+            return Ok(());
+        }
+
+        let mut pm = self.power_manager()?;
+        for &domain in domains {
+            let result = if on {
+                pm.power_domain_on(domain)
+            } else {
+                pm.power_domain_off(domain)
+            };
+            result.map_err(|_| RkNpuError::DomainNotFound)?;
+            self.track_power(domain, on);
+        }
         Ok(())
     }
 
-    /// 执行 AHB 总线复位
+    /// 记录电源域开关后各核心的供电状态；NPUTOP 下电时所有核心随之断电
+    fn track_power(&self, domain: PD, on: bool) {
+        if domain == NPUTOP {
+            self.nputop_powered.store(on, Ordering::Release);
+        }
+        let mask = if let Some(core) = core_for_domain(domain) {
+            core.mask_bit()
+        } else if domain == NPUTOP && !on {
+            u32::MAX
+        } else {
+            return;
+        };
+        if on {
+            self.powered_cores.fetch_or(mask, Ordering::AcqRel);
+        } else {
+            self.powered_cores.fetch_and(!mask, Ordering::AcqRel);
+        }
+    }
+
+    /// 核心所在电源域是否已上电
+    pub fn is_core_powered(&self, core: NpuCore) -> bool {
+        self.powered_cores.load(Ordering::Acquire) & core.mask_bit() != 0
+    }
+
+    /// 驱动记录的各电源域开关状态
     ///
-    /// AHB 复位会重置 NPU 的 AHB 总线接口
-    fn reset_ahb(&self) -> RkNpuResult<()> {
-        use crate::configs::cru_softrst::*;
+    /// `initialize` 后全部为开，`shutdown` 后全部为关。用于排查寄存器读数全为 0 等问题时
+    /// 确认驱动是否认为 NPU 已上电，以及避免重复开关电源域。
+    pub fn power_state(&self) -> PowerState {
+        let cores = self.powered_cores.load(Ordering::Acquire);
+        PowerState {
+            npu: cores & NpuCore::Npu0.mask_bit() != 0,
+            nputop: self.nputop_powered.load(Ordering::Acquire),
+            npu1: cores & NpuCore::Npu1.mask_bit() != 0,
+            npu2: cores & NpuCore::Npu2.mask_bit() != 0,
+        }
+    }
 
-        info!("[RKNPU] Performing AHB reset");
+    /// 单独打开一个核心的电源域，NPUTOP 须已上电（`initialize` 之后）
+    pub fn power_on_core(&self, core: NpuCore) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        self.set_power_domains(&[core_domain(core)], true)
+    }
 
-        // 只复位 NPU0 核心（当前只使用单核）
-        let reset_bit = NPU0_AHB_SRST;
+    /// 单独关闭一个核心的电源域，之后提交到该核心返回 `DomainNotFound`
+    /// 或在开启 `auto_power` 时重新上电
+    pub fn power_off_core(&self, core: NpuCore) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        if self.jobs.pending_on(core) != 0 {
+            return Err(RkNpuError::Busy);
+        }
+        self.set_power_domains(&[core_domain(core)], false)
+    }
 
-        // RK 芯片的写保护机制：高 16 位为写使能掩码
-        // 步骤 1: 置位 - 触发复位
-        let set_value = (1 << (reset_bit + WRITE_MASK_SHIFT)) | (1 << reset_bit);
-        self.cru_regs().softrst_con_npu.set(set_value);
+    /// 提交前确认目标核心已上电，未上电时按 `auto_power` 上电或报错
+    fn ensure_core_powered(&self, core: NpuCore) -> RkNpuResult<()> {
+        if self.is_core_powered(core) {
+            return Ok(());
+        }
+        if !self.auto_power {
+            error!("[RKNPU] {:?} is powered off", core);
+            return Err(RkNpuError::DomainNotFound);
+        }
+        info!("[RKNPU] Powering on {:?} for submit", core);
+        self.power_on_core(core)
+    }
 
-        // 步骤 2: 等待复位生效（至少 10us）
-        self.delay_us(10);
+    pub fn initialize(&mut self) -> RkNpuResult<()> {
+        self.set_power_domains(&[NPU1, NPU2, NPU, NPUTOP], true)?;
 
-        // 步骤 3: 清零 - 释放复位
-        let clear_value = (1 << (reset_bit + WRITE_MASK_SHIFT)) | (0 << reset_bit);
-        self.cru_regs().softrst_con_npu.set(clear_value);
+        // 清除上次启动（或 kexec 前）残留的中断，避免首个任务的等待立即返回
+        self.clear_interrupts()?;
 
-        // 步骤 4: 等待稳定
-        self.delay_us(5);
+        self.check_hardware_version()?;
 
-        info!("[RKNPU] AHB reset completed");
+        if self.self_test_on_init {
+            for core in self.available_cores() {
+                self.self_test(core)?;
+            }
+        }
+        self.consecutive_faults.store(0, Ordering::Release);
+        self.failed.store(false, Ordering::Release);
+        self.initialized = true;
         Ok(())
     }
 
-    /// 执行软复位
+    /// 从连续硬件错误导致的故障状态中恢复
     ///
-    /// 软复位会重置 NPU 的状态，包括：
-    /// 1. 清除中断状态
-    /// 2. 禁用所有使能位
-    /// 3. 执行 AXI 总线复位
-    /// 4. 执行 AHB 总线复位
+    /// 执行完整的 [`soft_reset`](Self::soft_reset) 并重新校验所有核心的版本，
+    /// 通过后清零错误计数并解除故障锁定。复位或校验失败时返回 `HardwareError`，
+    /// 设备保持故障状态。
+    pub fn clear_fault(&mut self) -> RkNpuResult<()> {
+        info!("[RKNPU] Clearing fault state");
+        let recovered = self
+            .soft_reset()
+            .and_then(|_| self.check_hardware_version());
+        if let Err(err) = recovered {
+            error!("[RKNPU] Device did not recover: {:?}", err);
+            return Err(RkNpuError::HardwareError);
+        }
+        self.consecutive_faults.store(0, Ordering::Release);
+        self.failed.store(false, Ordering::Release);
+        info!("[RKNPU] Fault state cleared");
+        Ok(())
+    }
+
+    /// 设备是否已完成 `initialize`
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// 未初始化时寄存器所在电源域可能尚未上电，访问会导致总线错误
+    fn ensure_initialized(&self) -> RkNpuResult<()> {
+        if !self.initialized {
+            error!("[RKNPU] Device used before initialize");
+            return Err(RkNpuError::NotInitialized);
+        }
+        Ok(())
+    }
+
+    /// 核心健康自检
     ///
-    /// 基于 C 驱动中的 rknpu_soft_reset() 函数实现
-    pub fn soft_reset(&self) -> RkNpuResult<()> {
-        info!("[RKNPU] Starting soft reset");
+    /// 在 `initialize` 之后调用，确认核心可以接收任务：
+    /// 1. 版本寄存器读数正确
+    /// 2. 没有挂起的中断（屏蔽后与原始状态均为 0）
+    /// 3. PC 引擎处于空闲状态
+    pub fn self_test(&self, core: NpuCore) -> RkNpuResult<()> {
+        let version = self.core_version(core)?;
 
-        // 1. 清除中断状态
-        self.clear_interrupts()?;
+        if let Err(err) = self.classify_version(version) {
+            error!(
+                "[RKNPU] Self test of {:?} failed: version 0x{:x}",
+                core, version
+            );
+            return Err(err);
+        }
 
-        // 2. 禁用所有使能位
-        // self.disable_enables()?;
+        let int_status = self.read_core(core, npu_reg::INT_STATUS);
+        let int_raw_status = self.read_core(core, npu_reg::INT_RAW_STATUS);
+        if int_status != 0 || int_raw_status != 0 {
+            error!(
+                "[RKNPU] Self test of {:?} failed: pending interrupts 0x{:x} (raw 0x{:x})",
+                core, int_status, int_raw_status
+            );
+            return Err(RkNpuError::HardwareError);
+        }
 
-        // 3. 执行 AXI 复位
-        self.reset_axi()?;
+        let pc_op_en = self.read_core(core, npu_reg::PC_OP_EN);
+        if pc_op_en != 0 {
+            error!(
+                "[RKNPU] Self test of {:?} failed: PC engine busy, pc_op_en={}",
+                core, pc_op_en
+            );
+            return Err(RkNpuError::HardwareError);
+        }
 
-        // 4. 执行 AHB 复位
-        self.reset_ahb()?;
+        debug!("[RKNPU] Self test of {:?} passed", core);
+        Ok(())
+    }
 
-        // 5. 等待复位完成
-        self.delay_us(10);
+    /// 安全地关闭设备
+    ///
+    /// 依次结束在途任务、停止 PC 引擎并清除使能位、屏蔽并清除中断，
+    /// 最后按与上电相反的顺序（子域在前）关闭 NPU 电源域。
+    /// 用于模块卸载或挂起；之后可再次调用 [`initialize`](Self::initialize) 重新上电并校验。
+    pub fn shutdown(&mut self) -> RkNpuResult<()> {
+        use crate::configs::INT_CLEAR_VALUE;
 
-        // Convert pm_base (usize) to NonNull<u8> expected by RockchipPM::new
-        let base_ptr = NonNull::new(self.pm_base as *mut u8)
-            .ok_or(RkNpuError::InvalidInput)?;
-        let mut pm = RockchipPM::new(base_ptr, rockchip_pm::RkBoard::Rk3588);
-        pm.power_domain_off(NPU1).unwrap();
-        pm.power_domain_off(NPU2).unwrap();
-        pm.power_domain_off(NPU).unwrap();
-        pm.power_domain_off(NPUTOP).unwrap();
+        info!("[RKNPU] Shutting down");
 
-        self.delay_us(1000); // 等待 1ms
+        for core in self.available_cores() {
+            // 1. 结束残留的在途任务，避免 fence 等待者永远挂起
+            self.complete_job(core, Err(RkNpuError::NotInitialized));
 
-        pm.power_domain_on(NPUTOP).unwrap();
-        pm.power_domain_on(NPU).unwrap();
-        pm.power_domain_on(NPU1).unwrap();
-        pm.power_domain_on(NPU2).unwrap();
+            // 2. 停止 PC 引擎并清除使能位
+            self.disable_enables(core)?;
 
-        info!("[RKNPU] Soft reset completed successfully");
+            // 3. 屏蔽并清除中断
+            self.write_core(core, npu_reg::INT_MASK, 0);
+            self.clear_int(core, INT_CLEAR_VALUE)?;
+        }
+
+        // 4. 关闭电源域
+        self.initialized = false;
+        self.set_power_domains(&[NPU2, NPU1, NPU, NPUTOP], false)?;
+
+        info!("[RKNPU] Shutdown completed");
+        Ok(())
+    }
+
+    /// 带宽优先级寄存器区域，板型不支持或未映射时返回 `NotSupported`
+    fn bw_priority_regs(&self) -> RkNpuResult<DirectMmio> {
+        let required = (BwPriority::MODE_OFFSET + size_of::<u32>()) as u32;
+        if !self.config.bw_enable || self.config.bw_priority_length < required || self.bw_base == 0
+        {
+            return Err(RkNpuError::NotSupported);
+        }
+        Ok(DirectMmio::new(self.bw_base))
+    }
+
+    /// 带宽优先级区域内 `offset` 处的寄存器，`bw_enable` 关闭、未映射或超出
+    /// `bw_priority_length` 时返回 `None`
+    fn bw_reg(&self, offset: usize) -> Option<DirectMmio> {
+        let end = (offset + size_of::<u32>()) as u32;
+        (self.config.bw_enable && self.config.bw_priority_length >= end && self.bw_base != 0)
+            .then(|| DirectMmio::new(self.bw_base))
+    }
+
+    /// 读取带宽区域寄存器，不可访问时返回 `None`
+    fn read_bw_reg(&self, offset: usize) -> Option<u32> {
+        self.bw_reg(offset).map(|base| base.read32(offset))
+    }
+
+    /// 写回 [`read_bw_reg`](Self::read_bw_reg) 保存的值，`None` 时不写
+    fn restore_bw_reg(&self, offset: usize, value: Option<u32>) {
+        if let (Some(base), Some(value)) = (self.bw_reg(offset), value) {
+            base.write32(offset, value);
+        }
+    }
+
+    /// 按结构化描述设置带宽优先级
+    pub fn set_bw_priority(&self, bw: BwPriority) -> RkNpuResult<()> {
+        let base = self.bw_priority_regs()?;
+        let (prio, mode) = bw.encode()?;
+        debug!(
+            "[RKNPU] Set bw priority {:?}: prio=0x{:x}, mode=0x{:x}",
+            bw, prio, mode
+        );
+        base.write32(BwPriority::PRIORITY_OFFSET, prio);
+        base.write32(BwPriority::MODE_OFFSET, mode);
+        Ok(())
+    }
+
+    /// 读取当前带宽优先级
+    pub fn bw_priority(&self) -> RkNpuResult<BwPriority> {
+        let base = self.bw_priority_regs()?;
+        let (prio, mode) = (
+            base.read32(BwPriority::PRIORITY_OFFSET),
+            base.read32(BwPriority::MODE_OFFSET),
+        );
+        Ok(BwPriority::decode(prio, mode))
+    }
+
+    pub fn rknpu_action_ioctl(&self, action: &mut RknpuAction) -> RkNpuResult<()> {
+        self.ensure_initialized()?;
+        let flag = RknpuActionFlag::try_from(action.flags).inspect_err(|_| {
+            error!("[RKNPU] Unknown action flag: 0x{:x}", action.flags);
+        })?;
+        match flag {
+            RknpuActionFlag::GetHwVersion => {
+                action.value = self.read_core(NpuCore::Npu0, npu_reg::VERSION);
+            }
+            RknpuActionFlag::GetFreq => {
+                action.value = self.read_npu_clk_hz()?;
+            }
+            RknpuActionFlag::SetFreq => {
+                self.set_npu_clk_hz(action.value)?;
+            }
+            RknpuActionFlag::GetTotalSramSize => {
+                action.value = self.sram.total() as u32;
+            }
+            RknpuActionFlag::GetFreeSramSize => {
+                action.value = self.free_sram_size() as u32;
+            }
+            RknpuActionFlag::SetProcNice => {
+                let nice = nice_from_action(action.value).inspect_err(|_| {
+                    info!("[RKNPU] Invalid nice value: {}", action.value as i32);
+                })?;
+                self.proc_nice.store(nice, Ordering::Relaxed);
+            }
+            RknpuActionFlag::ActClrTotalRwAmount => {
+                self.clear_all_rw_amount()?;
+            }
+            RknpuActionFlag::GetMaxSubmitNumber => {
+                // 超出 u32 时饱和为 u32::MAX
+                action.value = u32::try_from(self.config.max_submit_number).unwrap_or(u32::MAX);
+            }
+            RknpuActionFlag::ActReset => {
+                if action.value & RKNPU_RESET_POWER_CYCLE != 0 {
+                    debug!("[RKNPU] Performing full power-cycle reset");
+                    self.soft_reset()?;
+                } else {
+                    debug!("[RKNPU] Performing bus reset");
+                    self.bus_reset()?;
+                }
+            }
+            _ => {
+                error!("[RKNPU] Unsupported action flag: 0x{:x}", action.flags);
+                return Err(RkNpuError::InvalidInput);
+            }
+        }
         Ok(())
     }
+
+    /// 提交任务（`DRM_IOCTL_RKNPU_SUBMIT`）
+    ///
+    /// `submit.task_obj_addr` 为任务描述符的 DMA 地址，经 `dma_to_kernel` 转换后访问。
+    /// 已自行映射任务缓冲区的调用者可使用 [`rknpu_submit_kva`](Self::rknpu_submit_kva)。
+    /// 选中的核心上已有提交在执行时立即返回 `Busy`，不会阻塞等待。
+    pub fn rknpu_submit_ioctl(&self, submit: &mut RknpuSubmit) -> RkNpuResult<()> {
+        self.submit_ioctl_held(submit, 0).map(|_| ())
+    }
+
+    /// 与 [`rknpu_submit_ioctl`](Self::rknpu_submit_ioctl) 相同，额外返回任务从提交到
+    /// 完成的耗时（微秒）
+    ///
+    /// `RknpuSubmit` 没有存放耗时的字段，需要耗时的调用者使用这个入口；
+    /// 未安装 [`Clock`] 或 dry run 时为 `None`。
+    pub fn rknpu_submit_timed(&self, submit: &mut RknpuSubmit) -> RkNpuResult<Option<u64>> {
+        self.submit_ioctl_held(submit, 0)
+    }
+
+    /// [`rknpu_submit_ioctl`](Self::rknpu_submit_ioctl) 的实现，`held` 为调用者
+    /// 持有预留的核心掩码
+    fn submit_ioctl_held(&self, submit: &mut RknpuSubmit, held: u32) -> RkNpuResult<Option<u64>> {
+        self.ensure_initialized()?;
+        trace_debug!(
+            "[RKNPU] SUBMIT: task_obj_addr=0x{:x}, task_number={}, flags=0x{:x}, timeout={}, \
+             core_mask=0x{:x}",
+            submit.task_obj_addr,
+            submit.task_number,
+            submit.flags,
+            submit.timeout,
+            self.config.core_mask
+        );
+
+        let flags = self.validate_submit(submit)?;
+
+        if submit.task_obj_addr == 0 {
+            info!("[RKNPU] Invalid task_obj_addr: 0");
+            return Err(RkNpuError::InvalidTaskAddress);
+        }
+
+        self.check_task_range(submit)?;
+
+        let task_base = (self.dma_to_kernel)(pa!(submit.task_obj_addr as usize)).as_mut_ptr()
+            as *const RknpuTask;
+        self.submit_tasks(submit, flags, task_base, held)
+    }
+
+    /// 提交到指定的单个核心
+    ///
+    /// 等同于把 `submit.core_mask` 设为 `core.mask_bit()` 后调用
+    /// [`rknpu_submit_ioctl`](Self::rknpu_submit_ioctl)，原有的 `core_mask` 被覆盖。
+    /// 板型没有该核心时返回 `InvalidInput`。
+    pub fn submit_on(&self, core: NpuCore, submit: &mut RknpuSubmit) -> RkNpuResult<()> {
+        if !self.config.is_available(core) {
+            info!(
+                "[RKNPU] {:?} not available on {}",
+                core,
+                self.config.board.name()
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        submit.core_mask = core.mask_bit();
+        self.rknpu_submit_ioctl(submit)
+    }
+
+    /// 跳过参数校验的提交，供已自行校验过提交描述符的内核内调用者使用
+    ///
+    /// 与 [`rknpu_submit_ioctl`](Self::rknpu_submit_ioctl) 相比省去标志解析、任务数和
+    /// 任务范围检查以及入口日志，核心选择、任务登记和等待完成仍照常进行。
+    ///
+    /// # Safety
+    ///
+    /// 调用者必须保证：
+    /// - `submit.flags` 能通过 [`SubmitFlags::parse`]，`task_number` 在
+    ///   `1..=config.max_submit_number` 内；
+    /// - `task_obj_addr` 非零，`task_start..task_start + task_number` 全部落在任务缓冲区内；
+    /// - 任务描述符和命令缓冲区在提交期间保持有效，且内容已对设备可见
+    ///   （未设置 `COHERENT` 时由驱动刷新缓存）。
+    pub unsafe fn rknpu_submit_unchecked(&self, submit: &mut RknpuSubmit) -> RkNpuResult<()> {
+        self.ensure_initialized()?;
+        let flags = SubmitFlags::from_bits_unchecked(submit.flags);
+        let task_base = (self.dma_to_kernel)(pa!(submit.task_obj_addr as usize)).as_mut_ptr()
+            as *const RknpuTask;
+        self.submit_tasks(submit, flags, task_base, 0).map(|_| ())
+    }
+
+    /// 以内核虚拟地址提交任务，跳过 `dma_to_kernel` 转换
+    ///
+    /// 适用于任务缓冲区已由调用者映射到内核空间的情况；`task_kva` 指向任务数组起始
+    /// （即 `submit.task_obj_addr` 对应的位置），必须非空且 8 字节对齐。
+    /// 任务范围无法通过 [`BufferResolver`] 校验，由调用者保证不越界。
+    /// 命令缓冲区地址仍按 `regcmd_addr` 经 `dma_to_kernel` 转换后刷新。
+    pub fn rknpu_submit_kva(
+        &self,
+        submit: &mut RknpuSubmit,
+        task_kva: VirtAddr,
+    ) -> RkNpuResult<()> {
+        self.ensure_initialized()?;
+        trace_debug!(
+            "[RKNPU] SUBMIT (kva): task_kva={:?}, task_number={}, flags=0x{:x}",
+            task_kva,
+            submit.task_number,
+            submit.flags
+        );
+
+        let flags = self.validate_submit(submit)?;
+        let task_base = task_ptr(task_kva)?;
+        self.submit_tasks(submit, flags, task_base, 0).map(|_| ())
+    }
+
+    /// 读取并解码 `task_kva` 处的单个任务描述符，不做任何提交
+    ///
+    /// 供用户态工具核对生成的命令缓冲区与驱动的理解是否一致；
+    /// `task_kva` 的要求与 [`rknpu_submit_kva`](Self::rknpu_submit_kva) 相同。
+    pub fn inspect_task(&self, task_kva: VirtAddr) -> RkNpuResult<RknpuTaskView> {
+        let task = read_task(task_kva)?;
+        Ok(RknpuTaskView::from(&task))
+    }
+
+    /// 两个提交入口共用的参数校验
+    fn validate_submit(&self, submit: &RknpuSubmit) -> RkNpuResult<SubmitFlags> {
+        let flags = SubmitFlags::parse(submit.flags).inspect_err(|_| {
+            info!("[RKNPU] Invalid flags: 0x{:x}", submit.flags);
+        })?;
+
+        if submit.task_number == 0 {
+            info!("[RKNPU] Invalid task_number: 0");
+            return Err(RkNpuError::InvalidInput);
+        }
+
+        if submit.task_number as u64 > self.config.max_submit_number {
+            info!(
+                "[RKNPU] task_number {} exceeds max_submit_number {}",
+                submit.task_number, self.config.max_submit_number
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(flags)
+    }
+
+    /// 带 `ENABLE_MASK` 标志的提交所请求的使能位，取自首个任务的 `enable_mask`
+    ///
+    /// 超出 `config.enable_mask_allowed` 的位返回 `InvalidInput`
+    fn task_enable_mask(
+        &self,
+        task_base: *const RknpuTask,
+        submit: &RknpuSubmit,
+        flags: SubmitFlags,
+    ) -> RkNpuResult<Option<u32>> {
+        if !flags.has_enable_mask() {
+            return Ok(None);
+        }
+        if task_base.is_null() {
+            return Err(RkNpuError::InvalidTaskAddress);
+        }
+        let mask = unsafe {
+            let first_task = task_base.add(submit.task_start as usize);
+            core::ptr::read_unaligned(addr_of!((*first_task).enable_mask))
+        };
+        if mask & !self.config.enable_mask_allowed != 0 {
+            info!(
+                "[RKNPU] Invalid enable_mask 0x{:x}, allowed 0x{:x}",
+                mask, self.config.enable_mask_allowed
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(Some(mask))
+    }
+
+    /// 提交任务并统计连续的硬件错误
+    ///
+    /// 设置了 `fault_threshold` 时，连续出现该数量的 `HardwareError` 后锁定为故障状态，
+    /// 之后的提交直接返回 `DeviceFailed`，直到重新 `initialize` 或调用
+    /// [`clear_fault`](Self::clear_fault)。
+    fn submit_tasks(
+        &self,
+        submit: &mut RknpuSubmit,
+        flags: SubmitFlags,
+        task_base: *const RknpuTask,
+        held: u32,
+    ) -> RkNpuResult<Option<u64>> {
+        if self.failed.load(Ordering::Acquire) {
+            return Err(RkNpuError::DeviceFailed);
+        }
+        let result = self.dispatch_tasks(submit, flags, task_base, held);
+        match result {
+            Err(RkNpuError::HardwareError) => {
+                let faults = self.consecutive_faults.fetch_add(1, Ordering::AcqRel) + 1;
+                if self.fault_threshold != 0 && faults >= self.fault_threshold {
+                    error!(
+                        "[RKNPU] {} consecutive hardware errors, device marked as failed",
+                        faults
+                    );
+                    self.failed.store(true, Ordering::Release);
+                }
+            }
+            Ok(_) => self.consecutive_faults.store(0, Ordering::Release),
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// 选择核心、登记任务、写硬件并等待完成，返回测得的耗时
+    fn dispatch_tasks(
+        &self,
+        submit: &mut RknpuSubmit,
+        flags: SubmitFlags,
+        task_base: *const RknpuTask,
+        held: u32,
+    ) -> RkNpuResult<Option<u64>> {
+        if flags.is_dry_run() {
+            // 演练只做校验和寄存器值计算，不选择、占用或上电核心，也不登记任务
+            self.task_enable_mask(task_base, submit, flags)?;
+            self.dry_run_pc(task_base, submit, flags)?;
+            return Ok(None);
+        }
+        let core = self.select_core(submit.core_mask, flags.priority(), held)?;
+        trace_debug!("[RKNPU] Selected core {:?}", core);
+        let _busy = self.claim_core(core)?;
+        self.ensure_core_powered(core)?;
+
+        trace_debug!(
+            "[RKNPU] Checking interrupt status before submission: 0x{:x}",
+            self.int_status(core)?.bits()
+        );
+        trace_debug!(
+            "[RKNPU] Checking raw interrupt status: 0x{:x}",
+            self.int_raw_status(core)?.bits()
+        );
+
+        let fence = match self.fence_provider {
+            Some(provider) if flags.has_fence_out() => Some(provider.create_fence()?),
+            _ => None,
+        };
+        if let (Some(provider), Some(fence)) = (self.fence_provider, fence) {
+            // fence_fd 只有 i32，截断后用户等待的会是另一个 fence
+            match i32::try_from(fence.0) {
+                Ok(fd) => submit.fence_fd = fd,
+                Err(_) => {
+                    info!("[RKNPU] Fence id {} does not fit in fence_fd", fence.0);
+                    provider.signal_fence(fence, Err(RkNpuError::InvalidInput));
+                    return Err(RkNpuError::InvalidInput);
+                }
+            }
+        }
+        // 丢弃上一个任务结束后才到达的取消请求
+        self.abort_requested[core.index()].store(false, Ordering::Release);
+        if let Err(err) = self.jobs.begin(core, fence) {
+            info!("[RKNPU] Job table full ({} in flight)", MAX_INFLIGHT);
+            if let (Some(provider), Some(fence)) = (self.fence_provider, fence) {
+                provider.signal_fence(fence, Err(err));
+            }
+            return Err(err);
+        }
+
+        let enable_mask = match self.task_enable_mask(task_base, submit, flags) {
+            Ok(mask) => mask,
+            Err(err) => {
+                self.complete_job(core, Err(err));
+                return Err(err);
+            }
+        };
+        // 使能寄存器须在 pc_op_en 之前写好，任务结束后恢复
+        let saved_enable_mask = enable_mask.map(|mask| {
+            let saved = self.read_core(core, npu_reg::ENABLE_MASK);
+            self.write_core(core, npu_reg::ENABLE_MASK, mask);
+            saved
+        });
+
+        // 提交任务到硬件
+        let started_us = self.clock.map(|clock| clock.now_us());
+        if let Err(err) = self.job_commit_pc(core, task_base, submit, flags) {
+            if let Some(saved) = saved_enable_mask {
+                self.write_core(core, npu_reg::ENABLE_MASK, saved);
+            }
+            self.complete_job(core, Err(err));
+            return Err(err);
+        }
+
+        self.emit(NpuEvent::JobSubmitted {
+            core,
+            task_number: submit.task_number,
+        });
+
+        // 等待任务完成；RKNPU_TIMEOUT_INFINITE 原样传递，一直等到完成、错误或取消
+        let timeout = if submit.timeout > 0 {
+            submit.timeout
+        } else {
+            5000 // 默认5秒超时
+        };
+
+        let per_task =
+            Self::has_per_task_ints(task_base, submit).then_some((task_base, submit.task_start));
+        let result = self.wait_job_done(
+            core,
+            timeout,
+            submit.task_number,
+            per_task,
+            started_us,
+            task_descriptor_range(task_base, submit),
+        );
+        if let Some(saved) = saved_enable_mask {
+            self.write_core(core, npu_reg::ENABLE_MASK, saved);
+        }
+        // 超时等错误同样结束任务，释放槽位供后续提交使用
+        self.complete_job(core, result.map(|_| ()));
+        let elapsed_us = result?;
+
+        trace_debug!("[RKNPU] Task submission completed successfully");
+        Ok(elapsed_us)
+    }
+
+    /// 占用核心直到返回的守卫被 drop，核心已被其他提交占用时立即返回 `Busy`
+    ///
+    /// 提交是同步的，同一核心上的两个提交交错编程寄存器会互相破坏；这里用一次
+    /// compare-and-swap 代替锁，调用者收到 `Busy` 后自行退避或换核心重试。
+    fn claim_core(&self, core: NpuCore) -> RkNpuResult<CoreBusyGuard<'_>> {
+        let flag = &self.core_busy[core.index()];
+        if flag
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            info!("[RKNPU] {:?} busy with another submit", core);
+            return Err(RkNpuError::Busy);
+        }
+        Ok(CoreBusyGuard { flag })
+    }
+
+    /// 处理 `MEM_SYNC`：对 `obj_addr + offset` 起 `size` 字节执行缓存维护
+    ///
+    /// `obj_addr` 与提交路径上的地址一样是 DMA 地址，经 `dma_to_kernel` 转换后操作；
+    /// 设置了 `RKNPU_MEM_SYNC_BY_HANDLE` 时 `obj_addr` 为句柄，
+    /// 见 [`mem_sync_handle`](Self::mem_sync_handle)。
+    /// 方向由其余标志解码，无效方向返回 `InvalidInput`，`size` 为 0 时什么也不做。
+    pub fn rknpu_mem_sync_ioctl(&self, mem_sync: &RknpuMemSync) -> RkNpuResult<()> {
+        let dir = SyncDirection::from_flags(mem_sync.flags & !RKNPU_MEM_SYNC_BY_HANDLE)?;
+        if mem_sync.flags & RKNPU_MEM_SYNC_BY_HANDLE != 0 {
+            let handle = u32::try_from(mem_sync.obj_addr).map_err(|_| RkNpuError::InvalidInput)?;
+            return self.mem_sync_handle(handle, mem_sync.offset, mem_sync.size, dir);
+        }
+        if mem_sync.size == 0 {
+            return Ok(());
+        }
+        if mem_sync.obj_addr == 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        let start = mem_sync
+            .obj_addr
+            .checked_add(mem_sync.offset)
+            .filter(|start| start.checked_add(mem_sync.size).is_some())
+            .ok_or(RkNpuError::InvalidInput)?;
+        let kva = (self.dma_to_kernel)(pa!(start as usize));
+        self.mem_sync_scatter(&[(kva.as_usize(), mem_sync.size as usize)], dir)
+    }
+
+    /// 对句柄所指缓冲区中 `offset` 起 `len` 字节执行缓存维护
+    ///
+    /// 缓冲区经 [`NpuAllocator::get_handle`] 解析，其返回的起始地址按 DMA 地址处理；
+    /// 只同步输入等发生变化的区域，避免每次推理都刷新整块权重。
+    /// 未安装分配器返回 `NotSupported`，范围超出缓冲区返回 `InvalidInput`。
+    pub fn mem_sync_handle(
+        &self,
+        handle: u32,
+        offset: u64,
+        len: u64,
+        dir: SyncDirection,
+    ) -> RkNpuResult<()> {
+        let allocator = self.allocator.ok_or(RkNpuError::NotSupported)?;
+        let (base, size) = allocator.get_handle(handle)?;
+        let end = offset.checked_add(len).ok_or(RkNpuError::InvalidInput)?;
+        if end > size as u64 {
+            info!(
+                "[RKNPU] Sync range 0x{:x}+0x{:x} exceeds handle {} of 0x{:x} bytes",
+                offset, len, handle, size
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        if len == 0 {
+            return Ok(());
+        }
+        let start = base.checked_add(offset).ok_or(RkNpuError::InvalidInput)?;
+        let kva = (self.dma_to_kernel)(pa!(start as usize));
+        self.mem_sync_scatter(&[(kva.as_usize(), len as usize)], dir)
+    }
+
+    /// 校验 `task_start..task_start + task_number` 落在任务缓冲区内
+    ///
+    /// 缓冲区容纳的任务数由 [`BufferResolver`] 返回的长度除以 `RknpuTask` 大小得到；
+    /// 未安装时只检查范围计算不溢出。越界的 `task_start` 会让 PC 引擎读到垃圾数据甚至挂死。
+    fn check_task_range(&self, submit: &RknpuSubmit) -> RkNpuResult<()> {
+        let task_end = submit
+            .task_start
+            .checked_add(submit.task_number)
+            .ok_or(RkNpuError::InvalidInput)?;
+
+        let Some(resolver) = self.buffer_resolver else {
+            return Ok(());
+        };
+        let Some(len) = resolver.buffer_len(submit.task_obj_addr) else {
+            error!(
+                "[RKNPU] task_obj_addr 0x{:x} is not a known buffer",
+                submit.task_obj_addr
+            );
+            return Err(RkNpuError::InvalidTaskAddress);
+        };
+        let total_tasks = len / size_of::<RknpuTask>();
+        if submit.task_start as usize >= total_tasks || task_end as usize > total_tasks {
+            error!(
+                "[RKNPU] Task range {}..{} exceeds buffer of {} tasks",
+                submit.task_start, task_end, total_tasks
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// 校验要写入硬件的 DMA 地址不超出 `config.dma_mask_bits`
+    fn check_dma_addr(&self, name: &str, addr: u64) -> RkNpuResult<()> {
+        if !self.config.dma_addr_fits(addr) {
+            error!(
+                "[RKNPU] {} 0x{:x} exceeds {}-bit DMA range",
+                name, addr, self.config.dma_mask_bits
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// 写入 PC 引擎以 master DMA 取命令时使用的 DMA 基地址
+    ///
+    /// 仅 `config.pc_dma_ctrl` 非零的板型有该寄存器，其他板型返回 `NotSupported`；
+    /// 超出 `config.dma_mask_bits` 或 32 位寄存器范围的地址返回 `InvalidInput`
+    pub fn set_pc_dma_base(&self, core: NpuCore, phys: u64) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        if self.config.pc_dma_ctrl == 0 {
+            return Err(RkNpuError::NotSupported);
+        }
+        self.check_reg32_addr("pc_dma_base_addr", phys)?;
+        trace_debug!("[RKNPU] PC DMA base addr: 0x{:x}", phys);
+        self.write_core(core, npu_reg::PC_DMA_BASE_ADDR, phys as u32);
+        Ok(())
+    }
+
+    /// 写入任务 DMA 基地址后置位 `pc_dma_ctrl`，`config.pc_dma_ctrl` 为零的板型不写
+    fn program_pc_dma(&self, core: NpuCore, task_base_addr: u64) -> RkNpuResult<()> {
+        if self.config.pc_dma_ctrl == 0 {
+            return Ok(());
+        }
+        self.set_pc_dma_base(core, task_base_addr)?;
+        self.write_core(core, npu_reg::PC_DMA_CTRL, self.config.pc_dma_ctrl);
+        Ok(())
+    }
+
+    /// 校验要写入 32 位地址寄存器的 DMA 地址
+    ///
+    /// `pc_data_addr`、`pc_dma_base_addr` 都只有 32 位且没有对应的高位寄存器，
+    /// 即使 `dma_mask_bits` 为 40，高于 4GB 的地址写入后也会被截断，
+    /// 这类缓冲区必须经 IOMMU 映射到 4GB 以下或从低端内存分配
+    fn check_reg32_addr(&self, name: &str, addr: u64) -> RkNpuResult<()> {
+        self.check_dma_addr(name, addr)?;
+        if addr > u32::MAX as u64 {
+            error!(
+                "[RKNPU] {} 0x{:x} above 4GB cannot be programmed into a 32-bit register",
+                name, addr
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// 读取指定核心的版本寄存器
+    pub fn core_version(&self, core: NpuCore) -> RkNpuResult<u32> {
+        self.core_slot(core)?;
+        Ok(self.read_core(core, npu_reg::VERSION))
+    }
+
+    /// 读取指定核心的次版本号寄存器 `version_num`
+    pub fn core_version_num(&self, core: NpuCore) -> RkNpuResult<u32> {
+        self.core_slot(core)?;
+        Ok(self.read_core(core, npu_reg::VERSION_NUM))
+    }
+
+    /// 校验所有可用核心的版本，逐个报告不匹配的核心
+    ///
+    /// `version` 不符时失败；`version_num` 与 `config.expected_version_num` 不符时只告警
+    fn check_hardware_version(&self) -> RkNpuResult<()> {
+        let mut mismatched = 0u32;
+        let mut result = Ok(());
+        for core in self.available_cores() {
+            let version = self.core_version(core)?;
+            let Err(err) = self.classify_version(version) else {
+                self.check_version_num(core)?;
+                continue;
+            };
+            if err == RkNpuError::DeviceNotResponding {
+                error!(
+                    "[RKNPU] {:?} not responding: version 0x{:x}, check power domain and clocks",
+                    core, version
+                );
+            } else {
+                error!(
+                    "[RKNPU] {:?} version mismatch: 0x{:x} (expected 0x{:x})",
+                    core, version, self.config.expected_version
+                );
+            }
+            mismatched |= 1 << core.index();
+            // 无响应比版本不符更能说明问题，优先报告
+            if result.is_ok() || err == RkNpuError::DeviceNotResponding {
+                result = Err(err);
+            }
+        }
+        if mismatched != 0 {
+            error!("[RKNPU] Version check failed, core mask 0x{:x}", mismatched);
+        }
+        result
+    }
+
+    /// 比对核心的次版本号，不一致时只告警
+    fn check_version_num(&self, core: NpuCore) -> RkNpuResult<()> {
+        let version_num = self.core_version_num(core)?;
+        match self.config.expected_version_num {
+            Some(expected) if expected != version_num => warn!(
+                "[RKNPU] {:?} version_num 0x{:x} differs from expected 0x{:x}, \
+                 revision-specific features may not work",
+                core, version_num, expected
+            ),
+            _ => debug!("[RKNPU] {:?} version_num 0x{:x}", core, version_num),
+        }
+        Ok(())
+    }
+
+    /// 判断版本寄存器读数：全 0 或全 1 说明核心未上电或时钟未开，
+    /// 返回 `DeviceNotResponding`；其他与期望值不符的读数返回 `UnsupportedVersion`
+    fn classify_version(&self, version: u32) -> RkNpuResult<()> {
+        match version {
+            v if v == self.config.expected_version => Ok(()),
+            0 | u32::MAX => Err(RkNpuError::DeviceNotResponding),
+            _ => Err(RkNpuError::UnsupportedVersion),
+        }
+    }
+
+    /// PC 模式硬件任务提交
+    ///
+    /// 寄存器写入顺序：slave 模式切换 → `pc_data_addr` → `pc_data_amount` →
+    /// `int_mask` → `int_clear` → `pc_task_control` → `pc_dma_base_addr` →
+    /// `pc_dma_ctrl`（后两者仅 `config.pc_dma_ctrl` 非零的板型）→ `pc_op_en` 脉冲。
+    /// DMA 基地址和控制位必须在 `pc_op_en` 之前写入，否则 PC 引擎启动时会从旧地址取任务；
+    /// 控制位在基地址之后写入，避免引擎在基地址更新前按旧值取任务。
+    fn job_commit_pc(
+        &self,
+        core: NpuCore,
+        task_base: *const RknpuTask,
+        submit: &mut RknpuSubmit,
+        flags: SubmitFlags,
+    ) -> RkNpuResult<()> {
+        let regs = self.pc_job_regs(task_base, submit, flags)?;
+
+        unsafe {
+            if flags.is_coherent() && self.allow_coherent_submit {
+                trace_debug!("[RKNPU] Buffers already coherent, skipping cache flush");
+            } else {
+                let (tasks_start, tasks_len) = task_descriptor_range(task_base, submit);
+                dcache_flush_range(tasks_start, tasks_len);
+                for index in submit.task_start..=regs.task_end {
+                    let task = core::ptr::read_unaligned(task_base.add(index as usize));
+                    let (start, len) =
+                        command_buffer_range(&task).ok_or(RkNpuError::InvalidInput)?;
+                    let start_kva = (self.dma_to_kernel)(pa!(start as usize));
+                    dcache_flush_range(start_kva.as_usize(), len);
+                }
+            }
+        }
+
+        // 1. 切换到 slave 模式
+        self.enter_slave_mode(core)?;
+
+        // 2. 写 regcmd 地址（已确认不超过 32 位）
+        self.program_regcmd_addr(core, regs.regcmd_addr as u32);
+
+        // 3. 写数据量
+        trace_debug!("[RKNPU] Data amount: {}", regs.data_amount);
+        self.write_core(core, npu_reg::PC_DATA_AMOUNT, regs.data_amount);
+        // pc_data_amount 算错或写丢是 NPU 挂死最常见的原因，回读不一致时只告警，
+        // 附上计算依据便于排查，提交照常进行
+        if self.verify_writes {
+            let actual = self.read_core(core, npu_reg::PC_DATA_AMOUNT);
+            if actual != regs.data_amount {
+                warn!(
+                    "[RKNPU] pc_data_amount {} from regcfg_amount {} (scale {}) read back as {}, \
+                     check NPU clock and power",
+                    regs.data_amount, regs.regcfg_amount, self.config.pc_data_amount_scale, actual
+                );
+            }
+        }
+
+        // 4. 写中断掩码
+        self.write_core(core, npu_reg::INT_MASK, regs.int_mask);
+        self.verify_write(core, npu_reg::INT_MASK, regs.int_mask)?;
+
+        // 5. 清除中断
+        self.write_core(core, npu_reg::INT_CLEAR, regs.int_clear);
+
+        // 6. 写任务控制
+        trace_debug!("[RKNPU] PC task control: 0x{:x}", regs.task_control);
+        self.write_core(core, npu_reg::PC_TASK_CONTROL, regs.task_control);
+        self.verify_write(core, npu_reg::PC_TASK_CONTROL, regs.task_control)?;
+
+        // 7. 需要 PC DMA 控制的板型（如 RK3562）写任务 DMA 基地址和控制位
+        self.program_pc_dma(core, submit.task_base_addr)?;
+
+        // 8. 提交任务
+        if let Some(counter) = self.read_perf_counter(core) {
+            self.cycles.begin(core, counter);
+        }
+        self.queued_tasks[core.index()].store(submit.task_number, Ordering::Release);
+        self.pulse_pc_op(core);
+
+        trace_debug!("[RKNPU] Task submitted to hardware");
+        Ok(())
+    }
+
+    /// 演练提交：完成与 [`job_commit_pc`](Self::job_commit_pc) 相同的校验和计算，
+    /// 只记录将要写入的寄存器值，不访问硬件也不刷新缓存
+    fn dry_run_pc(
+        &self,
+        task_base: *const RknpuTask,
+        submit: &RknpuSubmit,
+        flags: SubmitFlags,
+    ) -> RkNpuResult<()> {
+        let regs = self.pc_job_regs(task_base, submit, flags)?;
+        info!(
+            "[RKNPU] Dry run: pc_data_addr=0x{:x} then 0x{:x}, pc_data_amount={}, \
+             int_mask=0x{:x}, int_clear=0x{:x}, pc_task_control=0x{:x}, \
+             pc_dma_base_addr={:x?}, pc_dma_ctrl={:x?}",
+            self.config.slave_mode_value,
+            regs.regcmd_addr as u32,
+            regs.data_amount,
+            regs.int_mask,
+            regs.int_clear,
+            regs.task_control,
+            (self.config.pc_dma_ctrl != 0).then_some(submit.task_base_addr as u32),
+            (self.config.pc_dma_ctrl != 0).then_some(self.config.pc_dma_ctrl)
+        );
+        Ok(())
+    }
+
+    /// 校验任务描述符并计算 PC 模式提交要写入的寄存器值，不访问硬件
+    fn pc_job_regs(
+        &self,
+        task_base: *const RknpuTask,
+        submit: &RknpuSubmit,
+        flags: SubmitFlags,
+    ) -> RkNpuResult<PcJobRegs> {
+        if task_base.is_null() {
+            return Err(RkNpuError::InvalidTaskAddress);
+        }
+
+        trace_debug!(
+            "[RKNPU] Committing PC job: task_base={:x}, task_start={}, task_number={}, \
+             flags=0x{:x}",
+            task_base as usize,
+            submit.task_start,
+            submit.task_number,
+            submit.flags
+        );
+
+        let task_end = submit
+            .task_start
+            .checked_add(submit.task_number)
+            .and_then(|end| end.checked_sub(1))
+            .ok_or(RkNpuError::InvalidInput)?;
+
+        unsafe {
+            let first_task = task_base.add(submit.task_start as usize);
+
+            for index in submit.task_start..=task_end {
+                let task = core::ptr::read_unaligned(task_base.add(index as usize));
+                let (start, _) = command_buffer_range(&task).ok_or(RkNpuError::InvalidInput)?;
+                self.check_dma_addr("regcmd_addr", start)?;
+            }
+
+            trace_debug!(
+                "[RKNPU] First task addr 0x{:x}, int_mask {}, regcmd_addr 0x{:x}",
+                first_task as usize,
+                core::ptr::read_unaligned(addr_of!((*first_task).int_mask)),
+                core::ptr::read_unaligned(addr_of!((*first_task).regcmd_addr))
+            );
+
+            trace_debug!("{:#?}", *first_task);
+
+            // 读取第一个任务的配置（使用 read_unaligned 因为是 packed struct）
+            let regcmd_addr = core::ptr::read_unaligned(addr_of!((*first_task).regcmd_addr));
+            let regcfg_amount = core::ptr::read_unaligned(addr_of!((*first_task).regcfg_amount));
+            let int_clear = core::ptr::read_unaligned(addr_of!((*first_task).int_clear));
+
+            // 先写入第一个任务的中断掩码，各任务掩码不同时等待过程中逐个更新
+            let int_mask = core::ptr::read_unaligned(addr_of!((*first_task).int_mask));
+
+            // 超出 DMA 位宽或 32 位寄存器宽度的地址写入后高位会被静默丢弃
+            self.check_reg32_addr("regcmd_addr", regcmd_addr)?;
+            if self.config.pc_dma_ctrl != 0 {
+                self.check_reg32_addr("task_base_addr", submit.task_base_addr)?;
+            }
+
+            let data_amount = self
+                .config
+                .compute_data_amount(regcfg_amount)
+                .ok_or(RkNpuError::InvalidInput)?;
+            let task_control = PcTaskControl::new()
+                .pingpong(flags.is_pingpong())
+                .task_number(submit.task_number)
+                .encode(&self.config)
+                .ok_or(RkNpuError::InvalidInput)?;
+
+            trace_debug!(
+                "[RKNPU] First task regcmd_addr=0x{:x}, regcfg_amount={}",
+                regcmd_addr,
+                regcfg_amount
+            );
+
+            Ok(PcJobRegs {
+                task_end,
+                regcmd_addr,
+                regcfg_amount,
+                data_amount,
+                int_mask,
+                int_clear,
+                task_control,
+            })
+        }
+    }
+
+    /// 让 PC 引擎进入 slave 模式，准备接收命令缓冲区地址
+    ///
+    /// 向 `pc_data_addr` 写入 `config.slave_mode_value` 后回读确认；
+    /// 卡死的 PC 引擎不会接受模式切换，此时返回 `HardwareError`。之后须由 [`program_regcmd_addr`](Self::program_regcmd_addr)
+    /// 写入真正的命令缓冲区地址。
+    fn enter_slave_mode(&self, core: NpuCore) -> RkNpuResult<()> {
+        let value = self.config.slave_mode_value;
+        self.write_core(core, npu_reg::PC_DATA_ADDR, value);
+        let mode = self.read_core(core, npu_reg::PC_DATA_ADDR);
+        if mode != value {
+            error!(
+                "[RKNPU] {:?} PC engine stuck, slave mode not acknowledged: pc_data_addr=0x{:x}",
+                core, mode
+            );
+            return Err(RkNpuError::HardwareError);
+        }
+        Ok(())
+    }
+
+    /// 在 slave 模式下写入首个任务的命令缓冲区地址
+    fn program_regcmd_addr(&self, core: NpuCore, regcmd_addr: u32) {
+        self.write_core(core, npu_reg::PC_DATA_ADDR, regcmd_addr);
+    }
+
+    /// 向 `pc_op_en` 写 1 再写 0，产生一个启动 PC 引擎的脉冲
+    ///
+    /// 引擎在上升沿锁存此前写入的任务配置并开始取命令，之后写 0 为下一次提交做准备。
+    /// 需要最小脉宽的板型通过 `config.pc_op_pulse_us` 在两次写入之间延时。
+    fn pulse_pc_op(&self, core: NpuCore) {
+        self.write_core(core, npu_reg::PC_OP_EN, 0x1);
+        if self.config.pc_op_pulse_us != 0 {
+            self.delay_us(self.config.pc_op_pulse_us);
+        }
+        self.write_core(core, npu_reg::PC_OP_EN, 0x0);
+    }
+
+    /// 开启 `verify_writes` 时回读寄存器并与写入值比较
+    fn verify_write(&self, core: NpuCore, offset: usize, expected: u32) -> RkNpuResult<()> {
+        if !self.verify_writes {
+            return Ok(());
+        }
+        let actual = self.read_core(core, offset);
+        if actual != expected {
+            error!(
+                "[RKNPU] {:?} register 0x{:x} write lost: expected 0x{:x}, read 0x{:x}",
+                core, offset, expected, actual
+            );
+            return Err(RkNpuError::HardwareError);
+        }
+        Ok(())
+    }
+
+    /// 轮询等待 `int_status` 中 `mask` 的任意一位置位
+    ///
+    /// 返回观察到的完整中断状态，不清除任何中断位；超时返回 `Timeout`。
+    /// 可用于等待任务完成、复位完成或错误等各类中断条件。
+    /// 安装了 [`Clock`] 时按实际经过的毫秒数判断超时，否则按轮询次数估算。
+    /// `timeout_ms` 为 [`RKNPU_TIMEOUT_INFINITE`] 时不超时，出现错误中断返回 `HardwareError`。
+    pub fn wait_int(&self, core: NpuCore, mask: u32, timeout_ms: u32) -> RkNpuResult<u32> {
+        self.poll_int(core, mask, timeout_ms, || None)
+    }
+
+    /// 等待任务完成，并在 PC 引擎完成的任务数变化时回调 `on_progress(completed, total)`
+    ///
+    /// 只在 `pc_task_status` 实际推进时调用回调，不会每次轮询都调用；
+    /// 完成时若最后一次回报的进度不是 `total`，再补报一次 `(total, total)`。
+    /// 返回观察到的完整中断状态，不清除中断位；超时返回 `Timeout`。
+    pub fn wait_job_done_with_progress(
+        &self,
+        core: NpuCore,
+        timeout_ms: u32,
+        total: u32,
+        on_progress: fn(completed: u32, total: u32),
+    ) -> RkNpuResult<u32> {
+        let mut reported = 0;
+        let int_status = self.poll_int(core, self.config.int_done_mask, timeout_ms, || {
+            if let Ok(completed) = self.pc_task_status(core)
+                && completed != reported
+            {
+                reported = completed;
+                on_progress(completed, total);
+            }
+            None
+        })?;
+        if reported != total {
+            on_progress(total, total);
+        }
+        Ok(int_status)
+    }
+
+    /// `wait_int` 的实现，每次轮询未命中时调用 `on_poll`
+    ///
+    /// `on_poll` 返回 `Some(status)` 时以该状态结束等待，用于中断位无法作为完成依据的情况
+    fn poll_int(
+        &self,
+        core: NpuCore,
+        mask: u32,
+        timeout_ms: u32,
+        mut on_poll: impl FnMut() -> Option<u32>,
+    ) -> RkNpuResult<u32> {
+        let slot = self.core_slot(core)?;
+
+        let mut deadline = (timeout_ms != RKNPU_TIMEOUT_INFINITE)
+            .then(|| Deadline::after_ms(self.clock, timeout_ms));
+        while !deadline.as_mut().is_some_and(Deadline::expired) {
+            let int_status = if self.poll_raw_status {
+                self.read_core(core, npu_reg::INT_RAW_STATUS)
+            } else {
+                self.read_core(core, npu_reg::INT_STATUS)
+            };
+            // 错误中断后完成位不会出现，不论是否设置了超时都立即返回，保留真实原因
+            if IntStatus(int_status).has_error() {
+                error!("[RKNPU] Error interrupt 0x{:x} on {:?}", int_status, core);
+                let _ = self.diagnose_hang(core);
+                return Err(RkNpuError::HardwareError);
+            }
+            if int_status & mask != 0 {
+                trace_debug!(
+                    "[RKNPU] Interrupt 0x{:x} observed, int_status=0x{:x}",
+                    mask,
+                    int_status
+                );
+                return Ok(int_status);
+            }
+            if self.abort_requested[slot].swap(false, Ordering::AcqRel) {
+                info!("[RKNPU] Wait on {:?} aborted", core);
+                return Err(RkNpuError::Aborted);
+            }
+            if let Some(int_status) = on_poll() {
+                return Ok(int_status);
+            }
+
+            // 简单延迟（实际延迟取决于系统）
+            for _ in 0..100 {
+                core::hint::spin_loop();
+            }
+        }
+
+        Err(RkNpuError::Timeout)
+    }
+
+    /// 各任务中断掩码不同的作业的等待
+    ///
+    /// PC 引擎依次执行任务列表，但 `int_mask`/`int_clear` 只有一份，不会随任务切换自动更新，
+    /// 提交时写入的是第一个任务的值。这里在 `pc_task_status` 推进到第 k 个任务时写入
+    /// 该任务的 `int_clear`（清除上一个任务留下的中间中断位）和 `int_mask`，
+    /// 使中间任务按各自的掩码上报；中间任务的掩码通常不含完成位，只有最后一个任务结束等待。
+    ///
+    /// 写入 `int_clear` 时最后一个任务可能已经完成，其完成位会被一并清除；
+    /// 因此已完成的任务数达到 `total` 时直接视为作业完成，返回当时的原始中断状态。
+    fn wait_job_done_per_task(
+        &self,
+        core: NpuCore,
+        timeout_ms: u32,
+        task_base: *const RknpuTask,
+        task_start: u32,
+        total: u32,
+    ) -> RkNpuResult<u32> {
+        let mut programmed = 0;
+        self.poll_int(core, self.config.int_done_mask, timeout_ms, || {
+            let completed = self.pc_task_status(core).ok()?;
+            if completed >= total {
+                return Some(self.read_core(core, npu_reg::INT_RAW_STATUS));
+            }
+            if completed == programmed {
+                return None;
+            }
+            programmed = completed;
+            let (int_mask, int_clear) = unsafe {
+                let task = task_base.add((task_start + completed) as usize);
+                (
+                    core::ptr::read_unaligned(addr_of!((*task).int_mask)),
+                    core::ptr::read_unaligned(addr_of!((*task).int_clear)),
+                )
+            };
+            trace_debug!(
+                "[RKNPU] Task {} started, int_mask=0x{:x}, int_clear=0x{:x}",
+                completed,
+                int_mask,
+                int_clear
+            );
+            self.write_core(core, npu_reg::INT_CLEAR, int_clear);
+            self.write_core(core, npu_reg::INT_MASK, int_mask);
+            None
+        })
+    }
+
+    /// 作业中是否有任务的 `int_mask`/`int_clear` 与第一个任务不同
+    fn has_per_task_ints(task_base: *const RknpuTask, submit: &RknpuSubmit) -> bool {
+        let ints = |index: u32| unsafe {
+            let task = task_base.add(index as usize);
+            (
+                core::ptr::read_unaligned(addr_of!((*task).int_mask)),
+                core::ptr::read_unaligned(addr_of!((*task).int_clear)),
+            )
+        };
+        let first = ints(submit.task_start);
+        (1..submit.task_number).any(|i| ints(submit.task_start + i) != first)
+    }
+
+    /// 等待任务完成
+    ///
+    /// `per_task` 为 `Some((task_base, task_start))` 时按任务更新中断掩码，
+    /// 见 [`wait_job_done_per_task`](Self::wait_job_done_per_task)；
+    /// `started_us` 为提交前的时钟读数，完成时据此计算并返回耗时，未安装时钟时为 `None`；
+    /// `task_range` 为任务描述符的范围，完成后使其缓存失效
+    fn wait_job_done(
+        &self,
+        core: NpuCore,
+        timeout_ms: u32,
+        task_number: u32,
+        per_task: Option<(*const RknpuTask, u32)>,
+        started_us: Option<u64>,
+        task_range: (usize, usize),
+    ) -> RkNpuResult<Option<u64>> {
+        trace_debug!(
+            "[RKNPU] Waiting for job completion (timeout: {}ms)",
+            timeout_ms
+        );
+
+        let waited = match per_task {
+            Some((task_base, task_start)) => {
+                self.wait_job_done_per_task(core, timeout_ms, task_base, task_start, task_number)
+            }
+            None => self.wait_job_done_with_progress(core, timeout_ms, task_number, |_, _| {}),
+        };
+        let int_status = match waited {
+            Ok(int_status) => int_status,
+            Err(RkNpuError::Timeout) => {
+                info!(
+                    "[RKNPU] Job timeout after {}ms, status=0x{:x}",
+                    timeout_ms,
+                    self.read_core(core, npu_reg::INT_STATUS)
+                );
+                self.emit(NpuEvent::JobTimeout { core, timeout_ms });
+                let _ = self.diagnose_hang(core);
+                self.stop_unfinished_job(core);
+                return Err(RkNpuError::TaskTimeout);
+            }
+            Err(RkNpuError::Aborted) => {
+                self.stop_unfinished_job(core);
+                return Err(RkNpuError::Aborted);
+            }
+            Err(err) => return Err(err),
+        };
+
+        // 只维护驱动读取的任务描述符，输出缓冲区由用户经 MEM_SYNC 自行维护
+        let (tasks_start, tasks_len) = task_range;
+        trace_debug!("dcache {:#x}+{:#x}", tasks_start, tasks_len);
+        unsafe {
+            dcache_invalidate_range(tasks_start, tasks_len);
+        }
+
+        self.clear_completion(core, int_status)?;
+
+        self.record_job_cycles(core);
+        let elapsed_us = self
+            .clock
+            .zip(started_us)
+            .map(|(clock, started)| clock.now_us().saturating_sub(started));
+        if let Some(elapsed) = elapsed_us {
+            self.stats.record_latency(core, elapsed);
+        }
+        self.emit(NpuEvent::JobCompleted {
+            core,
+            int_status,
+            elapsed_us,
+        });
+        Ok(elapsed_us)
+    }
+
+    /// 超时或取消后让核心回到可接收新任务的状态
+    ///
+    /// 任务表槽位由调用者以 `TaskTimeout`/`Aborted` 结束；这里只停止仍在运行的 PC 引擎，
+    /// 避免它在下一个任务提交后继续取旧命令。PC 引擎停不下来且开启了
+    /// `reset_on_timeout` 时升级为总线复位，复位会影响其他核心上的在途任务。
+    fn stop_unfinished_job(&self, core: NpuCore) {
+        if self.reset_pc_engine(core).is_ok() {
+            return;
+        }
+        if !self.reset_on_timeout {
+            error!("[RKNPU] {:?} PC engine still busy after timeout", core);
+            return;
+        }
+        if let Err(err) = self.bus_reset() {
+            error!(
+                "[RKNPU] Bus reset after timeout on {:?} failed: {:?}",
+                core, err
+            );
+        }
+    }
+
+    /// 取消指定核心上正在等待完成的任务
+    ///
+    /// 等待方在下一次轮询时返回 `Aborted` 并停止 PC 引擎。用于结束以
+    /// [`RKNPU_TIMEOUT_INFINITE`] 提交的任务；核心上没有在途任务时返回 `InvalidInput`。
+    pub fn abort_job(&self, core: NpuCore) -> RkNpuResult<()> {
+        let slot = self.core_slot(core)?;
+        if self.jobs.pending_on(core) == 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        info!("[RKNPU] Aborting job on {:?}", core);
+        self.abort_requested[slot].store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// 处理指定核心的中断，返回观察到的中断状态
+    ///
+    /// 状态为 0 时视为共享中断线上的伪中断，计入统计并返回 `Ok(0)`；
+    /// 含有未定义位时清除这些位，同时到达的已定义位照常清除并结束任务，
+    /// 最后返回 `UnrecognizedInterrupt`，便于操作系统分别统计伪中断和异常中断。中断频率异常时屏蔽相应中断并返回
+    /// `InterruptStorm`，操作系统应退避并复位该核心。
+    pub fn handle_irq(&self, core: NpuCore) -> RkNpuResult<u32> {
+        self.core_slot(core)?;
+        let int_status = self.read_core(core, npu_reg::INT_STATUS);
+        if int_status == 0 {
+            self.stats.record_spurious_irq();
+            return Ok(0);
+        }
+
+        let unrecognized = IntStatus(int_status).unrecognized();
+        if unrecognized != 0 {
+            error!(
+                "[RKNPU] Unrecognized interrupt on {:?}: 0x{:x}",
+                core, int_status
+            );
+            self.stats.record_unrecognized_irq();
+            self.clear_int(core, unrecognized)?;
+        }
+
+        let status = IntStatus(int_status & !unrecognized);
+        if !status.is_empty() {
+            self.clear_completion(core, status.bits())?;
+        }
+        self.check_irq_storm(core, int_status)?;
+        // 错误优先于完成；只有中间中断位时任务仍在执行，不结束任务
+        if status.has_error() {
+            error!("[RKNPU] Error interrupt on {:?}: 0x{:x}", core, int_status);
+            self.emit(NpuEvent::JobFailed {
+                core,
+                int_status: status.bits(),
+            });
+            self.complete_job(core, Err(RkNpuError::HardwareError));
+        } else if status.intersects(self.config.int_done_mask) {
+            self.record_job_cycles(core);
+            self.emit(NpuEvent::JobCompleted {
+                core,
+                int_status: status.bits(),
+                elapsed_us: None,
+            });
+            self.complete_job(core, Ok(()));
+        }
+
+        if unrecognized != 0 {
+            return Err(RkNpuError::UnrecognizedInterrupt);
+        }
+        Ok(int_status)
+    }
+
+    /// 统计核心的中断频率，超过 `IRQ_STORM_THRESHOLD` 时屏蔽 `int_status` 中的中断
+    ///
+    /// 在清除中断之后调用。中断位无法清除时 `handle_irq` 会被反复调用；屏蔽后返回 `InterruptStorm`，
+    /// 由上层退避并复位核心，避免在中断处理中活锁。复位后需重新设置 `int_mask`。
+    fn check_irq_storm(&self, core: NpuCore, int_status: u32) -> RkNpuResult<()> {
+        let slot = core.index();
+        let count = match self.clock {
+            Some(clock) => {
+                let now = clock.now_us();
+                let start = self.irq_window_start[slot].load(Ordering::Acquire);
+                if now.saturating_sub(start) >= IRQ_STORM_WINDOW_US {
+                    self.irq_window_start[slot].store(now, Ordering::Release);
+                    self.irq_window_count[slot].store(1, Ordering::Release);
+                    1
+                } else {
+                    self.irq_window_count[slot].fetch_add(1, Ordering::AcqRel) + 1
+                }
+            }
+            None => {
+                // 清除后中断仍然挂起才计数
+                if self.read_core(core, npu_reg::INT_RAW_STATUS) & int_status == 0 {
+                    self.irq_window_count[slot].store(0, Ordering::Release);
+                    return Ok(());
+                }
+                self.irq_window_count[slot].fetch_add(1, Ordering::AcqRel) + 1
+            }
+        };
+        if count <= IRQ_STORM_THRESHOLD {
+            return Ok(());
+        }
+
+        let int_mask = self.read_core(core, npu_reg::INT_MASK);
+        self.write_core(core, npu_reg::INT_MASK, int_mask & !int_status);
+        self.irq_window_count[slot].store(0, Ordering::Release);
+        error!(
+            "[RKNPU] Interrupt storm on {:?}: status 0x{:x} fired {} times, masked",
+            core, int_status, count
+        );
+        Err(RkNpuError::InterruptStorm)
+    }
+
+    /// 清零指定核心的读写数据量计数器（`dt_wr_amount`/`dt_rd_amount`/`wt_rd_amount`）
+    ///
+    /// 每个核心有独立的寄存器块，只影响 `core` 自身的计数器
+    pub fn clear_rw_amount(&self, core: NpuCore) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        // 与 C 驱动一致：先置位最高位触发清零，再撤销
+        self.write_core(core, npu_reg::CLR_ALL_RW_AMOUNT, 0x8000_0101);
+        self.write_core(core, npu_reg::CLR_ALL_RW_AMOUNT, 0x0000_0101);
+        Ok(())
+    }
+
+    /// 清零所有可用核心的读写数据量计数器
+    pub fn clear_all_rw_amount(&self) -> RkNpuResult<()> {
+        for core in self.available_cores() {
+            self.clear_rw_amount(core)?;
+        }
+        Ok(())
+    }
+
+    /// 设置指定核心的 `int_mask`（置位表示允许该中断）
+    ///
+    /// 写入后立即回读，硬件未锁存写入值时返回 `HardwareError`
+    pub fn set_int_mask(&self, core: NpuCore, mask: u32) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        self.write_core(core, npu_reg::INT_MASK, mask);
+        let actual = self.read_core(core, npu_reg::INT_MASK);
+        if actual != mask {
+            error!(
+                "[RKNPU] {:?} int_mask write lost: expected 0x{:x}, read 0x{:x}",
+                core, mask, actual
+            );
+            return Err(RkNpuError::HardwareError);
+        }
+        Ok(())
+    }
+
+    /// 确认指定核心的完成中断（`config.int_done_mask`）未被屏蔽
+    ///
+    /// 依赖中断判断任务完成之前调用；完成位全部被屏蔽时返回 `HardwareError`
+    pub fn verify_int_config(&self, core: NpuCore) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        let int_mask = self.read_core(core, npu_reg::INT_MASK);
+        if int_mask & self.config.int_done_mask == 0 {
+            error!(
+                "[RKNPU] {:?} completion interrupt masked: int_mask=0x{:x}, done mask=0x{:x}",
+                core, int_mask, self.config.int_done_mask
+            );
+            return Err(RkNpuError::HardwareError);
+        }
+        Ok(())
+    }
+
+    /// 读取指定核心经过 `int_mask` 屏蔽后的中断状态
+    pub fn int_status(&self, core: NpuCore) -> RkNpuResult<IntStatus> {
+        self.core_slot(core)?;
+        Ok(IntStatus(self.read_core(core, npu_reg::INT_STATUS)))
+    }
+
+    /// 读取指定核心的原始中断状态
+    ///
+    /// 不受 `int_mask` 影响，可用于排查中断已经产生但被屏蔽、
+    /// 导致任务始终无法通过屏蔽后的状态报告完成的问题。
+    pub fn int_raw_status(&self, core: NpuCore) -> RkNpuResult<IntStatus> {
+        self.core_slot(core)?;
+        Ok(IntStatus(self.read_core(core, npu_reg::INT_RAW_STATUS)))
+    }
+
+    /// 读取指定核心的 CBUF 配置
+    ///
+    /// 寄存器由任务的 regcmd 写入，反映的是最近一次执行的任务（分块时为最后一块）
+    /// 的划分方式，核心上电后尚未执行任务时为复位值。只读，驱动不修改 CBUF 配置。
+    pub fn cbuf_config(&self, core: NpuCore) -> RkNpuResult<CbufConfig> {
+        self.core_slot(core)?;
+        let con0 = self.read_core(core, npu_reg::CNA_CBUF_CON0);
+        let con1 = self.read_core(core, npu_reg::CNA_CBUF_CON1);
+        Ok(CbufConfig::from_regs(con0, con1))
+    }
+
+    /// 读取 PC 引擎已完成的任务数
+    ///
+    /// 任务状态寄存器的偏移因板型而异（见 `config.pc_task_status_offset`），
+    /// 因此不使用 `npu_reg::PC_TASK_STATUS` 这一固定偏移
+    pub fn pc_task_status(&self, core: NpuCore) -> RkNpuResult<u32> {
+        self.core_slot(core)?;
+        let status = self.read_core(core, self.config.pc_task_status_offset as usize);
+        Ok(status & self.config.pc_task_number_mask)
+    }
+
+    /// PC 引擎中排在当前任务之后、尚未开始执行的任务数
+    ///
+    /// PC 引擎按提交时的 `task_number` 依次取任务，`pc_task_status` 记录已完成的任务数，
+    /// 两者之差减去正在执行的一个即为排队深度。引擎没有单独的队列深度寄存器，
+    /// 因此该值由驱动记录的提交任务数推算，核心空闲时为 0。
+    pub fn task_queue_depth(&self, core: NpuCore) -> RkNpuResult<u32> {
+        let slot = self.core_slot(core)?;
+        let queued = self.queued_tasks[slot].load(Ordering::Acquire);
+        if queued == 0 || self.jobs.pending_on(core) == 0 {
+            return Ok(0);
+        }
+        let completed = self.pc_task_status(core)?;
+        Ok(queued.saturating_sub(completed).saturating_sub(1))
+    }
+
+    /// 丢弃 PC 引擎中尚未执行的任务
+    ///
+    /// 通过 [`reset_pc_engine`](Self::reset_pc_engine) 停止引擎，正在执行的任务一并终止；
+    /// 核心上有等待中的提交时，等待方返回 `Aborted`。
+    pub fn flush_task_queue(&self, core: NpuCore) -> RkNpuResult<()> {
+        let slot = self.core_slot(core)?;
+        info!("[RKNPU] Flushing task queue of {:?}", core);
+        if self.jobs.pending_on(core) != 0 {
+            self.abort_requested[slot].store(true, Ordering::Release);
+        }
+        self.queued_tasks[slot].store(0, Ordering::Release);
+        self.reset_pc_engine(core)
+    }
+
+    /// 读取指定核心的性能计数器
+    ///
+    /// 计数器在 NPU 核心时钟域计数，除以 [`read_npu_clk_hz`](Self::read_npu_clk_hz)
+    /// 即为秒数；板型没有计数器（`config.perf_counter_offset` 为 `None`）
+    /// 或核心不可用时返回 `None`
+    pub fn read_perf_counter(&self, core: NpuCore) -> Option<u32> {
+        let offset = self.config.perf_counter_offset?;
+        self.core_slot(core).ok()?;
+        Some(self.read_core(core, offset as usize))
+    }
+
+    fn record_job_cycles(&self, core: NpuCore) {
+        if let Some(counter) = self.read_perf_counter(core) {
+            self.cycles.end(core, counter);
+        }
+    }
+
+    /// 指定核心上一个经 `submit` 完成的任务从提交到完成的耗时（微秒）
+    ///
+    /// 由安装的 [`Clock`] 测量，包含寄存器编程和轮询等待的开销；
+    /// 未安装时钟或尚无完成任务时返回 `None`
+    pub fn last_job_elapsed_us(&self, core: NpuCore) -> Option<u64> {
+        self.stats.last_latency(core)
+    }
+
+    /// 指定核心上一个完成的任务消耗的周期数，没有计数器或尚无完成任务时返回 `None`
+    pub fn last_job_cycles(&self, core: NpuCore) -> Option<u64> {
+        self.cycles.last(core)
+    }
+
+    /// 采集指定核心的状态快照并记录日志，用于排查任务失败原因
+    ///
+    /// 超时和错误中断路径会在清除中断之前自动调用
+    pub fn diagnose_hang(&self, core: NpuCore) -> RkNpuResult<HangReport> {
+        self.core_slot(core)?;
+        let report = HangReport {
+            core,
+            job: self.jobs.current(core),
+            int_status: IntStatus(self.read_core(core, npu_reg::INT_STATUS)),
+            int_raw_status: IntStatus(self.read_core(core, npu_reg::INT_RAW_STATUS)),
+            int_mask: self.read_core(core, npu_reg::INT_MASK),
+            pc_op_en: self.read_core(core, npu_reg::PC_OP_EN),
+            pc_task_status: self.pc_task_status(core)?,
+            dt_wr_amount: self.read_core(core, npu_reg::DT_WR_AMOUNT),
+            dt_rd_amount: self.read_core(core, npu_reg::DT_RD_AMOUNT),
+            wt_rd_amount: self.read_core(core, npu_reg::WT_RD_AMOUNT),
+        };
+        error!("[RKNPU] {:#x?}", report);
+        Ok(report)
+    }
+
+    /// 强制让卡住的 PC 引擎回到空闲状态
+    ///
+    /// 关闭 `pc_op_en`、清除所有挂起中断，然后确认 PC 引擎不再推进任务状态。
+    /// 比 [`bus_reset`](Self::bus_reset) 更轻量，适用于部分失败后 PC 引擎卡住的情况；
+    /// 若引擎仍未回到空闲，返回 `HardwareError`，此时应升级为总线复位。
+    pub fn reset_pc_engine(&self, core: NpuCore) -> RkNpuResult<()> {
+        use crate::configs::INT_CLEAR_VALUE;
+
+        self.core_slot(core)?;
+        info!("[RKNPU] Resetting PC engine of {:?}", core);
+
+        self.write_core(core, npu_reg::PC_OP_EN, 0);
+        self.clear_int(core, INT_CLEAR_VALUE)?;
+
+        let before = self.pc_task_status(core)?;
+        self.delay_us(10);
+        let after = self.pc_task_status(core)?;
+        let op_en = self.read_core(core, npu_reg::PC_OP_EN);
+        let int_status = self.int_status(core)?;
+
+        if op_en != 0 || !int_status.is_empty() || before != after {
+            error!(
+                "[RKNPU] PC engine of {:?} still busy: pc_op_en={}, int_status=0x{:x}, \
+                 pc_task_status {} -> {}",
+                core,
+                op_en,
+                int_status.bits(),
+                before,
+                after
+            );
+            return Err(RkNpuError::HardwareError);
+        }
+
+        info!("[RKNPU] PC engine of {:?} is idle", core);
+        Ok(())
+    }
+
+    /// 清除指定的中断位
+    ///
+    /// `int_clear` 为写 1 清除，只写入 `mask` 中的位，其余中断保持挂起。
+    /// 这样错误处理路径确认错误中断时不会顺带清掉等待者尚未看到的完成中断。
+    pub fn clear_int(&self, core: NpuCore, mask: u32) -> RkNpuResult<()> {
+        self.core_slot(core)?;
+        self.write_core(core, npu_reg::INT_CLEAR, mask);
+        Ok(())
+    }
+
+    /// 按 `config.int_clear_strategy` 清除任务完成时观察到的中断
+    ///
+    /// `int_clear` 只写，读 `int_status` 与写 `int_clear` 之间可能有新中断到达。
+    /// 写回观察值时新位本就不受影响；写全部位的策略在写入前重读状态，从清除值中
+    /// 去掉新到达的位。清除后再读一次状态，新位保持挂起，留给下一次中断处理或等待。
+    fn clear_completion(&self, core: NpuCore, int_status: u32) -> RkNpuResult<()> {
+        let mut clear = self.config.int_clear_strategy.clear_value(int_status);
+        if clear != int_status {
+            clear &= !(self.read_core(core, npu_reg::INT_STATUS) & !int_status);
+        }
+        self.clear_int(core, clear)?;
+        let arrived = self.read_core(core, npu_reg::INT_STATUS) & !int_status;
+        if arrived != 0 {
+            debug!(
+                "[RKNPU] Interrupt 0x{:x} arrived on {:?} while clearing 0x{:x}, left pending",
+                arrived, core, int_status
+            );
+        }
+        Ok(())
+    }
+
+    /// 系统挂起：保存寄存器状态后关闭设备
+    pub fn suspend(&mut self) -> RkNpuResult<NpuSavedState> {
+        let mut state = NpuSavedState::default();
+        for core in self.available_cores() {
+            state.int_mask[core.index()] = self.read_core(core, npu_reg::INT_MASK);
+            state.enable_mask[core.index()] = self.read_core(core, npu_reg::ENABLE_MASK);
+        }
+        state.bw_priority = self.read_bw_reg(BwPriority::PRIORITY_OFFSET);
+        state.bw_mode = self.read_bw_reg(BwPriority::MODE_OFFSET);
+        state.bw_expect = self.read_bw_reg(BwPriority::EXPECT_OFFSET);
+        state.bw_tw = self.read_bw_reg(BwPriority::TW_OFFSET);
+        debug!("[RKNPU] Saved state: {:x?}", state);
+
+        self.shutdown()?;
+        Ok(state)
+    }
+
+    /// 系统恢复：重新上电并校验版本后恢复挂起前的寄存器状态
+    pub fn resume(&mut self, state: NpuSavedState) -> RkNpuResult<()> {
+        self.initialize()?;
+
+        for core in self.available_cores() {
+            self.write_core(core, npu_reg::INT_MASK, state.int_mask[core.index()]);
+            self.write_core(core, npu_reg::ENABLE_MASK, state.enable_mask[core.index()]);
+        }
+        self.restore_bw_reg(BwPriority::PRIORITY_OFFSET, state.bw_priority);
+        self.restore_bw_reg(BwPriority::MODE_OFFSET, state.bw_mode);
+        self.restore_bw_reg(BwPriority::EXPECT_OFFSET, state.bw_expect);
+        self.restore_bw_reg(BwPriority::TW_OFFSET, state.bw_tw);
+        debug!("[RKNPU] Restored state: {:x?}", state);
+        Ok(())
+    }
+
+    /// 读取并分类指定核心的中断，清除已确认的中断位
+    ///
+    /// 错误优先于完成：同时出现错误位和完成位时返回 `Error`。
+    /// 需要原始状态值的底层调用者仍可使用 [`handle_irq`](Self::handle_irq)。
+    pub fn poll_irq(&self, core: NpuCore) -> RkNpuResult<IrqEvent> {
+        let status = self.int_status(core)?;
+        if status.is_empty() {
+            self.stats.record_spurious_irq();
+            return Ok(IrqEvent::Spurious);
+        }
+        if status.has_error() {
+            let _ = self.diagnose_hang(core);
+        }
+        self.clear_int(core, status.bits())?;
+
+        if status.has_error() {
+            error!(
+                "[RKNPU] Error interrupt on {:?}: 0x{:x}",
+                core,
+                status.bits()
+            );
+            self.emit(NpuEvent::JobFailed {
+                core,
+                int_status: status.bits(),
+            });
+            self.complete_job(core, Err(RkNpuError::HardwareError));
+            return Ok(IrqEvent::Error { status });
+        }
+
+        if status.intersects(self.config.int_done_mask) {
+            self.record_job_cycles(core);
+            self.emit(NpuEvent::JobCompleted {
+                core,
+                int_status: status.bits(),
+                elapsed_us: None,
+            });
+            self.complete_job(core, Ok(()));
+            return Ok(IrqEvent::Done);
+        }
+
+        debug!(
+            "[RKNPU] Ignored interrupt on {:?}: 0x{:x}",
+            core,
+            status.bits()
+        );
+        Ok(IrqEvent::Spurious)
+    }
+
+    /// 从 CRU 时钟选择寄存器读取 NPU 当前的工作频率 (Hz)
+    ///
+    /// 根据父时钟选择和分频系数计算，反映硬件实际配置而非缓存值，
+    /// 计算方法见 [`cru_clksel`](crate::configs::cru_clksel)
+    pub fn read_npu_clk_hz(&self) -> RkNpuResult<u32> {
+        use crate::configs::{cru_clksel::*, cru_pll::pll_rate_hz};
+
+        let clksel = self.read_cru(cru_reg::CLKSEL_CON_NPU);
+        let mux = (clksel >> NPU_MUX_SHIFT) & ((1 << NPU_MUX_WIDTH) - 1);
+        if mux == NPU_MUX_NPLL {
+            // NPLL 可能已被 set_npu_clk_hz 重新配置，按寄存器实际值计算
+            let div = (clksel >> NPU_DIV_SHIFT) & ((1 << NPU_DIV_WIDTH) - 1);
+            let npll_hz = pll_rate_hz(
+                self.read_cru(cru_reg::NPLL_CON0),
+                self.read_cru(cru_reg::NPLL_CON1),
+            );
+            return Ok(npll_hz / (div + 1));
+        }
+        npu_clk_hz(clksel).ok_or_else(|| {
+            error!("[RKNPU] Unknown NPU clock parent, clksel=0x{:x}", clksel);
+            RkNpuError::HardwareError
+        })
+    }
+
+    /// 设置 NPU 时钟频率 (Hz)
+    ///
+    /// 超过 `config.max_npu_clk_hz` 的频率在访问 CRU 之前以 `InvalidInput` 拒绝。
+    /// 能由频率固定的父时钟整除得到时只切换选择器和分频；否则重新配置 NPLL：
+    /// 先把 NPU 停靠在 gpll 二分频（594MHz）上，NPLL 下电后写入 m/p/s 再上电，
+    /// 等待锁定后切回 NPLL。NPLL 锁定超时返回 `HardwareError`，此时 NPU 保持在停靠频率上。
+    pub fn set_npu_clk_hz(&self, hz: u32) -> RkNpuResult<()> {
+        use crate::configs::{cru_clksel::*, cru_pll::*, cru_softrst::WRITE_MASK_SHIFT};
+
+        if hz > self.config.max_npu_clk_hz {
+            error!(
+                "[RKNPU] NPU clock {} Hz above maximum {} Hz",
+                hz, self.config.max_npu_clk_hz
+            );
+            return Err(RkNpuError::InvalidInput);
+        }
+
+        if let Some((mux, div)) = fixed_parent_for(hz) {
+            info!(
+                "[RKNPU] NPU clock -> {} Hz (parent {}, div {})",
+                hz,
+                mux,
+                div + 1
+            );
+            self.write_cru(cru_reg::CLKSEL_CON_NPU, clksel_value(mux, div));
+            return Ok(());
+        }
+
+        let Some(params) = pll_params_for(hz) else {
+            error!("[RKNPU] NPU clock {} Hz cannot be generated", hz);
+            return Err(RkNpuError::InvalidInput);
+        };
+        info!("[RKNPU] NPU clock -> {} Hz via NPLL {:?}", hz, params);
+
+        // 重新配置期间让 NPU 以安全频率运行在 gpll 上
+        self.write_cru(cru_reg::CLKSEL_CON_NPU, clksel_value(0, NPU_PARK_DIV));
+
+        let hiword = |mask: u32, value: u32| (mask << WRITE_MASK_SHIFT) | value;
+        self.write_cru(cru_reg::NPLL_CON1, hiword(PLL_PWRDOWN, PLL_PWRDOWN));
+        self.write_cru(cru_reg::NPLL_CON0, hiword(PLL_M_MASK, params.m));
+        let ps_mask = PLL_P_MASK | (PLL_S_MASK << PLL_S_SHIFT);
+        self.write_cru(
+            cru_reg::NPLL_CON1,
+            hiword(ps_mask, params.p | (params.s << PLL_S_SHIFT)),
+        );
+        self.write_cru(cru_reg::NPLL_CON2, hiword(PLL_K_MASK, 0));
+        self.write_cru(cru_reg::NPLL_CON1, hiword(PLL_PWRDOWN, 0));
+
+        let mut deadline = Deadline::after_us(self.clock, PLL_LOCK_TIMEOUT_US);
+        while self.read_cru(cru_reg::NPLL_CON6) & PLL_LOCK == 0 {
+            if deadline.expired() {
+                error!("[RKNPU] NPLL failed to lock for {} Hz", hz);
+                return Err(RkNpuError::HardwareError);
+            }
+            for _ in 0..100 {
+                core::hint::spin_loop();
+            }
+        }
+
+        self.write_cru(cru_reg::CLKSEL_CON_NPU, clksel_value(NPU_MUX_NPLL, 0));
+        Ok(())
+    }
+
+    /// 关闭 NPU 时钟（空闲省电 / DVFS 切换前）
+    pub fn gate_npu_clocks(&self) {
+        use crate::configs::{cru_clkgate::NPU_CLK_GATE_MASK, cru_softrst::WRITE_MASK_SHIFT};
+
+        self.write_cru(
+            cru_reg::CLKGATE_CON_NPU,
+            (NPU_CLK_GATE_MASK << WRITE_MASK_SHIFT) | NPU_CLK_GATE_MASK,
+        );
+        debug!("[RKNPU] NPU clocks gated");
+    }
+
+    /// 打开 NPU 时钟
+    pub fn ungate_npu_clocks(&self) {
+        use crate::configs::{cru_clkgate::NPU_CLK_GATE_MASK, cru_softrst::WRITE_MASK_SHIFT};
+
+        self.write_cru(
+            cru_reg::CLKGATE_CON_NPU,
+            NPU_CLK_GATE_MASK << WRITE_MASK_SHIFT,
+        );
+        debug!("[RKNPU] NPU clocks ungated");
+    }
+
+    /// 微秒级延迟
+    fn delay_us(&self, us: u32) {
+        // 忙等待，安装了时钟时按实际时间计算
+        if self.clock.is_some() {
+            let mut deadline = Deadline::after_us(self.clock, us as u64);
+            while !deadline.expired() {
+                core::hint::spin_loop();
+            }
+            return;
+        }
+        for _ in 0..(us as u64) * 100 {
+            core::hint::spin_loop();
+        }
+    }
+
+    /// 清除所有可用核心的中断状态
+    fn clear_interrupts(&self) -> RkNpuResult<()> {
+        use crate::configs::INT_CLEAR_VALUE;
+        for core in self.available_cores() {
+            self.clear_int(core, INT_CLEAR_VALUE)?;
+        }
+        info!("[RKNPU] Interrupts cleared");
+        Ok(())
+    }
+
+    /// 禁用所有使能位
+    fn disable_enables(&self, core: NpuCore) -> RkNpuResult<()> {
+        // 禁用 PC 操作
+        self.write_core(core, npu_reg::PC_OP_EN, 0);
+        // 清除使能掩码
+        self.write_core(core, npu_reg::ENABLE_MASK, 0);
+        info!("[RKNPU] All enables of {:?} disabled", core);
+        Ok(())
+    }
+
+    /// 执行 AXI 总线复位
+    ///
+    /// AXI 复位会重置 NPU 的 AXI 总线接口
+    fn reset_axi(&self) -> RkNpuResult<()> {
+        info!("[RKNPU] Performing AXI reset");
+        self.reset_cores(ResetKind::Axi)?;
+        info!("[RKNPU] AXI reset completed");
+        Ok(())
+    }
+
+    /// 执行 AHB 总线复位
+    ///
+    /// AHB 复位会重置 NPU 的 AHB 总线接口
+    fn reset_ahb(&self) -> RkNpuResult<()> {
+        info!("[RKNPU] Performing AHB reset");
+        self.reset_cores(ResetKind::Ahb)?;
+        info!("[RKNPU] AHB reset completed");
+        Ok(())
+    }
+
+    /// 同时复位所有可用核心（至多 `config.num_resets` 个）的指定总线
+    ///
+    /// 例如 RK3583 只切换 NPU0/NPU1 的复位位，NPU2 的复位位保持不变
+    fn reset_cores(&self, kind: ResetKind) -> RkNpuResult<()> {
+        use crate::configs::cru_softrst::*;
+
+        let reset_bits = self
+            .available_cores()
+            .take(self.config.num_resets)
+            .fold(0u32, |bits, core| bits | (1 << srst_bit(core, kind)));
+        debug!("[RKNPU] {:?} reset bits: 0x{:x}", kind, reset_bits);
+
+        // RK 芯片的写保护机制：高 16 位为写使能掩码
+        // 步骤 1: 置位 - 触发复位
+        let set_value = (reset_bits << WRITE_MASK_SHIFT) | reset_bits;
+        self.write_cru(cru_reg::SOFTRST_CON_NPU, set_value);
+
+        // 步骤 2: 等待复位生效
+        self.delay_us(self.config.reset_assert_us);
+
+        // 步骤 3: 清零 - 释放复位
+        let clear_value = reset_bits << WRITE_MASK_SHIFT;
+        self.write_cru(cru_reg::SOFTRST_CON_NPU, clear_value);
+
+        // 步骤 4: 等待稳定
+        self.delay_us(self.config.reset_settle_us);
+
+        Ok(())
+    }
+
+    /// 执行总线复位
+    ///
+    /// 只复位 NPU 的总线接口，不对电源域下电，包括：
+    /// 1. 清除中断状态
+    /// 2. 执行 AXI 总线复位
+    /// 3. 执行 AHB 总线复位
+    ///
+    /// 电源域保持上电，SRAM 内容不会丢失，适合从总线挂死中快速恢复。
+    /// 恢复能力弱于 [`soft_reset`](Self::soft_reset)：若 NPU 内部状态
+    /// 已损坏，总线复位后仍可能无法工作，此时应使用完整的软复位。
+    pub fn bus_reset(&self) -> RkNpuResult<()> {
+        info!("[RKNPU] Starting bus reset");
+
+        self.reset_bus_interfaces()?;
+
+        info!("[RKNPU] Bus reset completed");
+        self.emit(NpuEvent::ResetPerformed { power_cycle: false });
+        Ok(())
+    }
+
+    /// 总线复位的具体步骤，由 `bus_reset` 和 `soft_reset` 共用
+    fn reset_bus_interfaces(&self) -> RkNpuResult<()> {
+        // 1. 清除中断状态
+        self.clear_interrupts()?;
+
+        // 2. 禁用所有使能位
+        // self.disable_enables(NpuCore::Npu0)?;
+
+        // 3. 执行 AXI 复位
+        self.reset_axi()?;
+
+        // 4. 执行 AHB 复位
+        self.reset_ahb()?;
+
+        // 5. 等待复位完成
+        self.delay_us(10);
+
+        Ok(())
+    }
+
+    /// 执行软复位
+    ///
+    /// 软复位会重置 NPU 的状态，包括：
+    /// 1. 执行总线复位（见 [`bus_reset`](Self::bus_reset)）
+    /// 2. 所有 NPU 电源域下电后重新上电
+    ///
+    /// 电源域下电会丢失 SRAM 内容，开销也较大，但能恢复总线复位无法
+    /// 处理的内部状态错误，是最强的恢复手段。
+    ///
+    /// 基于 C 驱动中的 rknpu_soft_reset() 函数实现
+    pub fn soft_reset(&self) -> RkNpuResult<()> {
+        info!("[RKNPU] Starting soft reset");
+
+        self.reset_bus_interfaces()?;
+
+        self.set_power_domains(&[NPU1, NPU2, NPU, NPUTOP], false)?;
+
+        self.delay_us(1000); // 等待 1ms
+
+        self.set_power_domains(&[NPUTOP, NPU, NPU1, NPU2], true)?;
+
+        info!("[RKNPU] Soft reset completed successfully");
+        self.emit(NpuEvent::ResetPerformed { power_cycle: true });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{vec, vec::Vec};
+
+    use super::*;
+    use crate::mmio::mock::MockMmio;
+
+    struct NoopPower;
+
+    impl PowerControl for NoopPower {
+        fn power_on(&self, _domain: PD) -> RkNpuResult<()> {
+            Ok(())
+        }
+
+        fn power_off(&self, _domain: PD) -> RkNpuResult<()> {
+            Ok(())
+        }
+    }
+
+    static NOOP_POWER: NoopPower = NoopPower;
+
+    /// 记录收到的事件
+    #[derive(Default)]
+    struct RecordingEvents(std::sync::Mutex<Vec<NpuEvent>>);
+
+    impl RecordingEvents {
+        fn leak() -> &'static Self {
+            std::boxed::Box::leak(std::boxed::Box::default())
+        }
+
+        fn events(&self) -> Vec<NpuEvent> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl EventSink for RecordingEvents {
+        fn on_event(&self, event: NpuEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    /// 记录每次触发的 fence 及其结果
+    #[derive(Default)]
+    struct RecordingFences {
+        next: std::sync::atomic::AtomicU64,
+        signaled: std::sync::Mutex<Vec<(FenceId, RkNpuResult<()>)>>,
+    }
+
+    impl RecordingFences {
+        fn leak() -> &'static Self {
+            std::boxed::Box::leak(std::boxed::Box::default())
+        }
+
+        fn signaled(&self) -> Vec<(FenceId, RkNpuResult<()>)> {
+            self.signaled.lock().unwrap().clone()
+        }
+    }
+
+    impl FenceProvider for RecordingFences {
+        fn create_fence(&self) -> RkNpuResult<FenceId> {
+            Ok(FenceId(self.next.fetch_add(1, Ordering::Relaxed) + 1))
+        }
+
+        fn signal_fence(&self, fence: FenceId, result: RkNpuResult<()>) {
+            self.signaled.lock().unwrap().push((fence, result));
+        }
+    }
+
+    fn identity(addr: PhysAddr) -> VirtAddr {
+        VirtAddr::from(addr.as_usize())
+    }
+
+    /// 核心寄存器在模拟 NPU 区域中的偏移
+    fn reg(core: NpuCore, offset: usize) -> usize {
+        core.index() * NPU_CORE_SIZE + offset
+    }
+
+    /// 寄存器由模拟区域提供、电源操作为空操作的设备
+    fn mock_dev(board: RkBoard) -> (RknpuDev, &'static MockMmio, &'static MockMmio) {
+        let mut dev = RknpuDev::new(0, 0, 0, board, identity);
+        let npu = MockMmio::npu();
+        let cru = MockMmio::cru();
+        dev.set_mmio(npu, cru);
+        dev.set_power_control(&NOOP_POWER);
+        (dev, npu, cru)
+    }
+
+    /// 已完成 `initialize` 的模拟设备，写入记录已清空
+    fn initialized_dev(board: RkBoard) -> (RknpuDev, &'static MockMmio, &'static MockMmio) {
+        let (mut dev, npu, cru) = mock_dev(board);
+        let cores: Vec<_> = dev.available_cores().collect();
+        for core in cores {
+            npu.set(reg(core, npu_reg::VERSION), dev.config.expected_version);
+        }
+        dev.initialize().unwrap();
+        npu.clear_writes();
+        cru.clear_writes();
+        (dev, npu, cru)
+    }
+
+    fn task(regcfg_amount: u32, regcmd_addr: u64) -> RknpuTask {
+        RknpuTask {
+            flags: 0,
+            op_idx: 0,
+            enable_mask: 0,
+            int_mask: 0,
+            int_clear: 0,
+            int_status: 0,
+            regcfg_amount,
+            regcfg_offset: 0,
+            regcmd_addr,
+        }
+    }
+
+    /// 提交到 `core` 的单任务 PC 提交，完成中断在启动任务后到达
+    fn completing_submit(dev: &RknpuDev, npu: &MockMmio, core: NpuCore) -> (RknpuSubmit, VirtAddr) {
+        // 与用户态运行时一样，提交时清除所有中断位，包括上一个任务留下的错误位
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([RknpuTask {
+            int_clear: crate::configs::INT_CLEAR_VALUE,
+            ..task(16, 0x3000_0000)
+        }]));
+        npu.arrive_on_start(reg(core, npu_reg::PC_OP_EN), dev.config.int_done_mask);
+        let submit = RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_number: 1,
+            core_mask: core.mask_bit(),
+            ..Default::default()
+        };
+        (submit, VirtAddr::from(tasks.as_ptr() as usize))
+    }
+
+    #[test]
+    fn command_buffer_range_covers_every_regcfg_entry() {
+        for amount in [0, 1, 112, 0x1000] {
+            let (start, len) = command_buffer_range(&task(amount, 0x3000_0000)).unwrap();
+            assert_eq!(start, 0x3000_0000);
+            assert_eq!(
+                len,
+                (amount + RKNPU_PC_DATA_EXTRA_AMOUNT) as usize * REGCMD_ENTRY_SIZE
+            );
+        }
+        assert_eq!(command_buffer_range(&task(u32::MAX, 0)), None);
+    }
+
+    #[test]
+    fn task_descriptor_range_covers_only_submitted_tasks() {
+        let tasks = [task(0, 0); 8];
+        let base = tasks.as_ptr();
+        let size = size_of::<RknpuTask>();
+        let submit = |task_start, task_number| RknpuSubmit {
+            task_start,
+            task_number,
+            ..Default::default()
+        };
+        assert_eq!(
+            task_descriptor_range(base, &submit(0, 1)),
+            (base as usize, size)
+        );
+        assert_eq!(
+            task_descriptor_range(base, &submit(3, 5)),
+            (base as usize + 3 * size, 5 * size)
+        );
+    }
+
+    fn coalesced(ranges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let mut out = Vec::new();
+        for_each_coalesced(ranges, |start, end| out.push((start, end)));
+        out
+    }
+
+    #[test]
+    fn mem_sync_scatter_coalesces_adjacent_and_overlapping_ranges() {
+        // 相邻、重叠以及落在同一缓存行内的区间合并为一个
+        assert_eq!(
+            coalesced(&[(0x1000, 0x100), (0x1100, 0x100), (0x1180, 0x200)]),
+            vec![(0x1000, 0x1380)]
+        );
+        assert_eq!(
+            coalesced(&[(0x1000, 0x10), (0x1030, 0x10)]),
+            vec![(0x1000, 0x1040)]
+        );
+        // 起始地址向下对齐到缓存行
+        assert_eq!(coalesced(&[(0x1024, 0x8)]), vec![(0x1000, 0x102c)]);
+    }
+
+    #[test]
+    fn mem_sync_scatter_keeps_disjoint_ranges_in_order() {
+        assert_eq!(
+            coalesced(&[(0x3000, 0x40), (0x1000, 0x40), (0x1040, 0x40)]),
+            vec![(0x3000, 0x3040), (0x1000, 0x1080)]
+        );
+        assert_eq!(coalesced(&[(0x1000, 0), (0x2000, 0)]), vec![]);
+        // 末尾饱和到地址空间上限
+        assert_eq!(
+            coalesced(&[(usize::MAX - 0x10, 0x100)]),
+            vec![(usize::MAX - 0x3f, usize::MAX)]
+        );
+    }
+
+    #[test]
+    fn register_access_goes_through_installed_mmio() {
+        let (dev, npu, _) = mock_dev(RkBoard::Rk3588);
+        npu.set(reg(NpuCore::Npu1, npu_reg::VERSION), 0x1234);
+        assert_eq!(dev.core_version(NpuCore::Npu1), Ok(0x1234));
+
+        dev.clear_int(NpuCore::Npu2, 0x5).unwrap();
+        assert_eq!(
+            npu.writes(),
+            vec![(reg(NpuCore::Npu2, npu_reg::INT_CLEAR), 0x5)]
+        );
+        assert_eq!(
+            npu.writes_to(reg(NpuCore::Npu0, npu_reg::INT_CLEAR)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn npu_clk_above_maximum_is_rejected_before_touching_cru() {
+        let (dev, _, cru) = mock_dev(RkBoard::Rk3588);
+        assert_eq!(
+            dev.set_npu_clk_hz(1_500_000_000),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert!(cru.writes().is_empty());
+    }
+
+    #[test]
+    fn npll_relock_parks_npu_on_safe_divider() {
+        use crate::configs::{cru_clksel::*, cru_pll::PLL_LOCK};
+
+        let (dev, _, cru) = mock_dev(RkBoard::Rk3588);
+        cru.set(cru_reg::NPLL_CON6, PLL_LOCK);
+        dev.set_npu_clk_hz(900_000_000).unwrap();
+        assert_eq!(
+            cru.writes_to(cru_reg::CLKSEL_CON_NPU),
+            vec![clksel_value(0, NPU_PARK_DIV), clksel_value(NPU_MUX_NPLL, 0)]
+        );
+    }
+
+    #[test]
+    fn pc_dma_ctrl_written_after_base_only_on_rk3562() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3562);
+        dev.program_pc_dma(NpuCore::Npu0, 0x1000_0000).unwrap();
+        assert_eq!(
+            npu.writes(),
+            vec![
+                (reg(NpuCore::Npu0, npu_reg::PC_DMA_BASE_ADDR), 0x1000_0000),
+                (
+                    reg(NpuCore::Npu0, npu_reg::PC_DMA_CTRL),
+                    dev.config.pc_dma_ctrl
+                ),
+            ]
+        );
+
+        for board in RkBoard::ALL.into_iter().filter(|&b| b != RkBoard::Rk3562) {
+            let (dev, npu, _) = initialized_dev(board);
+            dev.program_pc_dma(NpuCore::Npu0, 0x1000_0000).unwrap();
+            assert!(npu.writes().is_empty(), "{:?}", board);
+        }
+    }
+
+    #[test]
+    fn suspend_resume_round_trip() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3568);
+        let bw: &'static mut [u32; 4] = std::boxed::Box::leak(std::boxed::Box::new([0; 4]));
+        let mut dev = dev.with_bw_priority_base(bw.as_mut_ptr() as usize);
+        dev.write_core(NpuCore::Npu0, npu_reg::INT_MASK, 0x300);
+        dev.write_core(NpuCore::Npu0, npu_reg::ENABLE_MASK, 0xd);
+        dev.set_bw_priority(BwPriority {
+            read: 2,
+            write: 1,
+            priority: 3,
+        })
+        .unwrap();
+        let base = DirectMmio::new(bw.as_ptr() as usize);
+        base.write32(BwPriority::EXPECT_OFFSET, 0x40);
+        base.write32(BwPriority::TW_OFFSET, 0x80);
+
+        let state = dev.suspend().unwrap();
+        assert_eq!(state.bw_expect, Some(0x40));
+        assert_eq!(state.bw_tw, Some(0x80));
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_MASK), 0);
+        npu.set(reg(NpuCore::Npu0, npu_reg::ENABLE_MASK), 0);
+        for offset in (0..16).step_by(4) {
+            base.write32(offset, 0);
+        }
+
+        dev.resume(state).unwrap();
+        assert_eq!(npu.get(reg(NpuCore::Npu0, npu_reg::INT_MASK)), 0x300);
+        assert_eq!(npu.get(reg(NpuCore::Npu0, npu_reg::ENABLE_MASK)), 0xd);
+        assert_eq!(
+            dev.bw_priority(),
+            Ok(BwPriority {
+                read: 2,
+                write: 1,
+                priority: 3
+            })
+        );
+        assert_eq!(base.read32(BwPriority::EXPECT_OFFSET), 0x40);
+        assert_eq!(base.read32(BwPriority::TW_OFFSET), 0x80);
+    }
+
+    #[test]
+    fn bw_registers_not_saved_without_bw_enable() {
+        let (dev, _, _) = initialized_dev(RkBoard::Rk3583);
+        let mut dev = dev.with_bw_priority_base(0x1000);
+        let state = dev.suspend().unwrap();
+        assert_eq!(state.bw_priority, None);
+        assert_eq!(state.bw_tw, None);
+    }
+
+    #[test]
+    fn reserved_cores_excluded_from_auto_selection() {
+        let (dev, _, _) = initialized_dev(RkBoard::Rk3588);
+        let reservation = dev.reserve_core(NpuCore::Npu0).unwrap();
+        for _ in 0..8 {
+            let core = dev.select_core(0, SubmitPriority::Normal, 0).unwrap();
+            assert_ne!(core, NpuCore::Npu0);
+        }
+
+        let npu0 = NpuCore::Npu0.mask_bit();
+        assert_eq!(
+            dev.select_core(npu0, SubmitPriority::Normal, 0),
+            Err(RkNpuError::Busy)
+        );
+        assert_eq!(
+            dev.select_core(npu0, SubmitPriority::Normal, npu0),
+            Ok(NpuCore::Npu0)
+        );
+
+        drop(reservation);
+        assert_eq!(
+            dev.select_core(npu0, SubmitPriority::Normal, 0),
+            Ok(NpuCore::Npu0)
+        );
+    }
+
+    #[test]
+    fn clear_int_writes_only_the_given_bits() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_STATUS), 0x301);
+        dev.clear_int(NpuCore::Npu0, 0x100).unwrap();
+        assert_eq!(
+            npu.writes(),
+            vec![(reg(NpuCore::Npu0, npu_reg::INT_CLEAR), 0x100)]
+        );
+        // 未写入的位保持挂起
+        assert_eq!(dev.int_status(NpuCore::Npu0).unwrap().bits(), 0x201);
+    }
+
+    #[test]
+    fn is_busy_until_completion_bit_is_raised() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        // 没有在途任务时即使状态寄存器为 0 也视为空闲
+        assert!(!dev.is_busy(NpuCore::Npu0));
+
+        dev.jobs.begin(NpuCore::Npu0, None).unwrap();
+        assert!(dev.is_busy(NpuCore::Npu0));
+        assert!(!dev.is_busy(NpuCore::Npu1));
+
+        npu.set(
+            reg(NpuCore::Npu0, npu_reg::INT_STATUS),
+            dev.config.int_done_mask,
+        );
+        assert!(!dev.is_busy(NpuCore::Npu0));
+    }
+
+    #[test]
+    fn verify_int_config_requires_unmasked_completion() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3583);
+        let done = dev.config.int_done_mask;
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_MASK), done);
+        assert_eq!(dev.verify_int_config(NpuCore::Npu0), Ok(()));
+
+        npu.set(reg(NpuCore::Npu1, npu_reg::INT_MASK), !done);
+        assert_eq!(
+            dev.verify_int_config(NpuCore::Npu1),
+            Err(RkNpuError::HardwareError)
+        );
+
+        // RK3583 没有 Npu2
+        assert_eq!(
+            dev.verify_int_config(NpuCore::Npu2),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert!(npu.writes().is_empty());
+    }
+
+    #[test]
+    fn clear_rw_amount_writes_only_the_targeted_core() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.clear_rw_amount(NpuCore::Npu1).unwrap();
+        let clr = reg(NpuCore::Npu1, npu_reg::CLR_ALL_RW_AMOUNT);
+        assert_eq!(npu.writes(), vec![(clr, 0x8000_0101), (clr, 0x0000_0101)]);
+
+        npu.clear_writes();
+        dev.clear_all_rw_amount().unwrap();
+        for core in [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2] {
+            assert_eq!(
+                npu.writes_to(reg(core, npu_reg::CLR_ALL_RW_AMOUNT)).len(),
+                2
+            );
+        }
+    }
+
+    #[test]
+    fn enter_slave_mode_detects_stuck_pc_engine() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let slave = dev.config.slave_mode_value;
+        assert_eq!(dev.enter_slave_mode(NpuCore::Npu0), Ok(()));
+        assert_eq!(
+            npu.writes(),
+            vec![(reg(NpuCore::Npu0, npu_reg::PC_DATA_ADDR), slave)]
+        );
+
+        // 卡死的 PC 引擎保留上一个任务的命令缓冲区地址
+        let pc_data_addr = reg(NpuCore::Npu1, npu_reg::PC_DATA_ADDR);
+        npu.set(pc_data_addr, 0x3000_0000);
+        npu.stick(pc_data_addr);
+        assert_eq!(
+            dev.enter_slave_mode(NpuCore::Npu1),
+            Err(RkNpuError::HardwareError)
+        );
+        assert_eq!(npu.writes_to(pc_data_addr), vec![slave]);
+    }
+
+    #[test]
+    fn raw_status_polling_sees_masked_completion() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let done = dev.config.int_done_mask;
+        // 中断处理程序屏蔽了 int_mask：原始状态有完成位，屏蔽后的状态为 0
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_RAW_STATUS), done | 0x4);
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_STATUS), 0);
+        assert_eq!(
+            dev.int_raw_status(NpuCore::Npu0).unwrap().bits(),
+            done | 0x4
+        );
+        assert_eq!(dev.int_status(NpuCore::Npu0).unwrap().bits(), 0);
+
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, 1),
+            Err(RkNpuError::Timeout)
+        );
+        let dev = dev.with_raw_status_polling(true);
+        assert_eq!(dev.wait_int(NpuCore::Npu0, done, 1), Ok(done | 0x4));
+    }
+
+    #[test]
+    fn pc_data_addr_gets_slave_mode_then_regcmd_addr() {
+        for board in RkBoard::ALL {
+            let (dev, npu, _) = initialized_dev(board);
+            let slave = dev.config.slave_mode_value;
+            dev.enter_slave_mode(NpuCore::Npu0).unwrap();
+            dev.program_regcmd_addr(NpuCore::Npu0, 0x3000_0000);
+            assert_eq!(
+                npu.writes(),
+                vec![
+                    (reg(NpuCore::Npu0, npu_reg::PC_DATA_ADDR), slave),
+                    (reg(NpuCore::Npu0, npu_reg::PC_DATA_ADDR), 0x3000_0000),
+                ],
+                "{:?}",
+                board
+            );
+        }
+    }
+
+    fn total_sram_size(dev: &RknpuDev) -> u32 {
+        let mut action = RknpuAction {
+            flags: RknpuActionFlag::GetTotalSramSize as u32,
+            value: 0,
+        };
+        dev.rknpu_action_ioctl(&mut action).unwrap();
+        action.value
+    }
+
+    #[test]
+    fn nbuf_override_rejects_misaligned_region() {
+        let dev = || {
+            RknpuDev::new(
+                0x1000_0000,
+                0x2000_0000,
+                0x3000_0000,
+                RkBoard::Rk3588,
+                identity,
+            )
+        };
+        for (phyaddr, size) in [(0x4000_0800, 0x1000), (0x4000_0000, 0x1800)] {
+            assert_eq!(
+                dev().with_nbuf(phyaddr, size).err(),
+                Some(RkNpuError::InvalidInput),
+                "0x{:x}+0x{:x}",
+                phyaddr,
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn nbuf_override_checks_configured_register_bases() {
+        let dev = || {
+            RknpuDev::new(
+                0x1000_0000,
+                0x2000_0000,
+                0x3000_0000,
+                RkBoard::Rk3588,
+                identity,
+            )
+        };
+        // 与最后一个核心的寄存器窗口、CRU、PMU 重叠
+        for phyaddr in [0x1002_0000, 0x2000_0000, 0x2fff_f000] {
+            assert_eq!(
+                dev().with_nbuf(phyaddr, 0x2000).err(),
+                Some(RkNpuError::InvalidInput),
+                "0x{:x}",
+                phyaddr
+            );
+        }
+        // 参考设计的 NPU 地址在这块板上不是寄存器区域
+        assert!(dev().with_nbuf(NPU0_BASE as u64, 0x1000).is_ok());
+    }
+
+    #[test]
+    fn nbuf_override_reflected_in_total_sram_size() {
+        let (dev, _, _) = initialized_dev(RkBoard::Rk3588);
+        let dev = dev.with_nbuf(0x4000_0000, 0x8000).unwrap();
+        assert_eq!(dev.config.nbuf_phyaddr, 0x4000_0000);
+        assert_eq!(total_sram_size(&dev), 0x8000);
+
+        let dev = dev.with_nbuf(0, 0).unwrap();
+        assert_eq!(total_sram_size(&dev), 0);
+    }
+
+    #[test]
+    fn max_submit_number_saturates_to_u32() {
+        let (mut dev, _, _) = initialized_dev(RkBoard::Rk3588);
+        let mut action = RknpuAction {
+            flags: RknpuActionFlag::GetMaxSubmitNumber as u32,
+            value: 0,
+        };
+        dev.rknpu_action_ioctl(&mut action).unwrap();
+        assert_eq!(action.value as u64, dev.config.max_submit_number);
+
+        dev.config.max_submit_number = u32::MAX as u64 + 1;
+        dev.rknpu_action_ioctl(&mut action).unwrap();
+        assert_eq!(action.value, u32::MAX);
+    }
+
+    #[test]
+    fn dry_run_leaves_hardware_untouched() {
+        let (dev, npu, cru) = initialized_dev(RkBoard::Rk3588);
+        let dev = dev.with_auto_power(true);
+        dev.power_off_core(NpuCore::Npu1).unwrap();
+        npu.clear_writes();
+        cru.clear_writes();
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([task(16, 0x3000_0000)]));
+        let task_kva = VirtAddr::from(tasks.as_ptr() as usize);
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC | SubmitFlags::DRY_RUN,
+            task_number: 1,
+            task_obj_addr: tasks.as_ptr() as u64,
+            core_mask: NpuCore::Npu1.mask_bit(),
+            ..Default::default()
+        };
+
+        // 核心被占用且未上电，演练仍然只做校验
+        let busy = dev.claim_core(NpuCore::Npu1).unwrap();
+        assert_eq!(dev.rknpu_submit_timed(&mut submit), Ok(None));
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        drop(busy);
+        assert!(npu.writes().is_empty());
+        assert!(cru.writes().is_empty());
+        assert!(!dev.is_core_powered(NpuCore::Npu1));
+        assert_eq!(dev.current_job(NpuCore::Npu1), None);
+
+        // 校验照常进行
+        tasks[0] = task(16, 0x1_0000_0000);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn pc_data_amount_mismatch_only_warns() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let dev = dev.with_verify_writes(true);
+        let tasks = [task(16, 0x3000_0000)];
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_number: 1,
+            ..Default::default()
+        };
+        // 写入被总线丢弃，回读得到旧值
+        let data_amount = reg(NpuCore::Npu0, npu_reg::PC_DATA_AMOUNT);
+        npu.stick(data_amount);
+
+        let flags = SubmitFlags::parse(submit.flags).unwrap();
+        assert_eq!(
+            dev.job_commit_pc(NpuCore::Npu0, tasks.as_ptr(), &mut submit, flags),
+            Ok(())
+        );
+        assert_eq!(npu.get(data_amount), 0);
+        assert_eq!(
+            npu.writes_to(reg(NpuCore::Npu0, npu_reg::PC_OP_EN)),
+            vec![0x1, 0x0]
+        );
+    }
+
+    #[test]
+    fn pulse_pc_op_writes_one_then_zero() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.pulse_pc_op(NpuCore::Npu1);
+        let pc_op_en = reg(NpuCore::Npu1, npu_reg::PC_OP_EN);
+        assert_eq!(npu.writes(), vec![(pc_op_en, 0x1), (pc_op_en, 0x0)]);
+        assert_eq!(npu.get(pc_op_en), 0);
+    }
+
+    #[test]
+    fn interrupt_arriving_mid_clear_stays_pending() {
+        use crate::configs::IntClearStrategy;
+
+        for strategy in [IntClearStrategy::WriteObserved, IntClearStrategy::WriteAll] {
+            let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+            let dev = dev.with_int_clear_strategy(strategy);
+            let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+            npu.set(status, IntStatus::DPU);
+            // handle_irq 读到 DPU 完成后，PPU 中断在写 int_clear 之前到达
+            npu.arrive_after_status_read(status, IntStatus::PPU);
+
+            assert_eq!(dev.handle_irq(NpuCore::Npu0), Ok(IntStatus::DPU));
+            assert_eq!(npu.get(status), IntStatus::PPU, "{:?}", strategy);
+            let clears = npu.writes_to(reg(NpuCore::Npu0, npu_reg::INT_CLEAR));
+            assert!(
+                clears.iter().all(|&clear| clear & IntStatus::PPU == 0),
+                "{:?}",
+                strategy
+            );
+        }
+    }
+
+    #[test]
+    fn concurrent_submit_to_same_core_gets_busy() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([task(0, 0x3000_0000)]));
+        let task_kva = VirtAddr::from(tasks.as_ptr() as usize);
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_number: 1,
+            core_mask: NpuCore::Npu0.mask_bit(),
+            ..Default::default()
+        };
+
+        // 第一个提交仍在编程寄存器或等待完成
+        let first = dev.claim_core(NpuCore::Npu0).unwrap();
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::Busy)
+        );
+        assert!(npu.writes().is_empty());
+        assert_eq!(dev.current_job(NpuCore::Npu0), None);
+
+        // 其他核心不受影响
+        assert!(dev.claim_core(NpuCore::Npu1).is_ok());
+        drop(first);
+        assert!(dev.claim_core(NpuCore::Npu0).is_ok());
+    }
+
+    #[test]
+    fn handle_irq_fails_fence_on_error_interrupt() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let fences = RecordingFences::leak();
+        dev.set_fence_provider(fences);
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+
+        let fence = fences.create_fence().unwrap();
+        dev.jobs.begin(NpuCore::Npu0, Some(fence)).unwrap();
+        // 中间中断位不结束任务
+        npu.set(status, IntStatus::CNA_CSC);
+        assert_eq!(dev.handle_irq(NpuCore::Npu0), Ok(IntStatus::CNA_CSC));
+        assert!(fences.signaled().is_empty());
+        assert!(dev.current_job(NpuCore::Npu0).is_some());
+
+        npu.set(status, IntStatus::DPU | IntStatus::DMA_READ_ERROR);
+        dev.handle_irq(NpuCore::Npu0).unwrap();
+        assert_eq!(
+            fences.signaled(),
+            vec![(fence, Err(RkNpuError::HardwareError))]
+        );
+        assert_eq!(dev.current_job(NpuCore::Npu0), None);
+
+        let fence = fences.create_fence().unwrap();
+        dev.jobs.begin(NpuCore::Npu0, Some(fence)).unwrap();
+        npu.set(status, dev.config.int_done_mask);
+        dev.handle_irq(NpuCore::Npu0).unwrap();
+        assert_eq!(fences.signaled().last(), Some(&(fence, Ok(()))));
+    }
+
+    #[test]
+    fn handle_irq_reports_failed_jobs_as_failed() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let events = RecordingEvents::leak();
+        dev.set_event_sink(events);
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+
+        npu.set(status, IntStatus::CNA_CSC);
+        dev.handle_irq(NpuCore::Npu0).unwrap();
+        assert!(events.events().is_empty());
+
+        let failed = IntStatus::DPU | IntStatus::DMA_WRITE_ERROR;
+        npu.set(status, failed);
+        dev.handle_irq(NpuCore::Npu0).unwrap();
+        npu.set(status, IntStatus::DPU);
+        dev.handle_irq(NpuCore::Npu0).unwrap();
+        assert_eq!(
+            events.events(),
+            vec![
+                NpuEvent::JobFailed {
+                    core: NpuCore::Npu0,
+                    int_status: failed,
+                },
+                NpuEvent::JobCompleted {
+                    core: NpuCore::Npu0,
+                    int_status: IntStatus::DPU,
+                    elapsed_us: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_irq_completes_done_bit_arriving_with_unrecognized_bits() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let fences = RecordingFences::leak();
+        dev.set_fence_provider(fences);
+        let fence = fences.create_fence().unwrap();
+        dev.jobs.begin(NpuCore::Npu0, Some(fence)).unwrap();
+
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+        npu.set(status, IntStatus::DPU | 1 << 20);
+        assert_eq!(
+            dev.handle_irq(NpuCore::Npu0),
+            Err(RkNpuError::UnrecognizedInterrupt)
+        );
+        assert_eq!(npu.get(status), 0);
+        assert_eq!(fences.signaled(), vec![(fence, Ok(()))]);
+        assert_eq!(dev.current_job(NpuCore::Npu0), None);
+    }
+
+    #[test]
+    fn wait_int_reports_error_interrupt_with_finite_timeout() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let done = dev.config.int_done_mask;
+        npu.set(
+            reg(NpuCore::Npu0, npu_reg::INT_STATUS),
+            IntStatus::DMA_READ_ERROR,
+        );
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, 1),
+            Err(RkNpuError::HardwareError)
+        );
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, RKNPU_TIMEOUT_INFINITE),
+            Err(RkNpuError::HardwareError)
+        );
+    }
+
+    #[test]
+    fn error_interrupt_with_finite_timeout_runs_diagnosis() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        npu.set(
+            reg(NpuCore::Npu0, npu_reg::INT_STATUS),
+            IntStatus::DMA_WRITE_ERROR,
+        );
+        // 只有 diagnose_hang 读取数据量计数器
+        let dt_wr_amount = reg(NpuCore::Npu0, npu_reg::DT_WR_AMOUNT);
+        assert_eq!(npu.reads_of(dt_wr_amount), 0);
+        assert_eq!(
+            dev.wait_job_done(NpuCore::Npu0, 1000, 1, None, None, (0, 0)),
+            Err(RkNpuError::HardwareError)
+        );
+        assert_eq!(npu.reads_of(dt_wr_amount), 1);
+    }
+
+    /// 读数由测试设定的时钟
+    struct MockClock(AtomicU64);
+
+    impl Clock for MockClock {
+        fn now_us(&self) -> u64 {
+            self.0.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn elapsed_time_measured_with_clock() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let clock: &'static MockClock =
+            std::boxed::Box::leak(std::boxed::Box::new(MockClock(AtomicU64::new(1_000))));
+        dev.set_clock(clock);
+        let done = dev.config.int_done_mask;
+
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_STATUS), done);
+        clock.0.store(1_750, Ordering::Relaxed);
+        assert_eq!(
+            dev.wait_job_done(NpuCore::Npu0, 1000, 1, None, Some(1_000), (0, 0)),
+            Ok(Some(750))
+        );
+        assert_eq!(dev.last_job_elapsed_us(NpuCore::Npu0), Some(750));
+    }
+
+    #[test]
+    fn elapsed_time_is_none_without_clock() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        npu.set(
+            reg(NpuCore::Npu0, npu_reg::INT_STATUS),
+            dev.config.int_done_mask,
+        );
+        assert_eq!(
+            dev.wait_job_done(NpuCore::Npu0, 1000, 1, None, None, (0, 0)),
+            Ok(None)
+        );
+        assert_eq!(dev.last_job_elapsed_us(NpuCore::Npu0), None);
+    }
+
+    #[test]
+    fn completion_exits_the_no_timeout_loop() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let done = dev.config.int_done_mask;
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+        // 第一次轮询未命中，完成位随后到达
+        npu.arrive_after_status_read(status, done);
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, RKNPU_TIMEOUT_INFINITE),
+            Ok(done)
+        );
+    }
+
+    /// 任务缓冲区长度固定的缓冲区查询，统计查询次数
+    struct CountingResolver {
+        len: usize,
+        lookups: AtomicU32,
+    }
+
+    impl BufferResolver for CountingResolver {
+        fn buffer_len(&self, _dma_addr: u64) -> Option<usize> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+            Some(self.len)
+        }
+    }
+
+    /// 安装了缓冲区查询的模拟设备，`task_obj_addr` 指向任务数组
+    fn resolved_submit(
+        core: NpuCore,
+    ) -> (
+        RknpuDev,
+        &'static MockMmio,
+        &'static CountingResolver,
+        RknpuSubmit,
+    ) {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let resolver = std::boxed::Box::leak(std::boxed::Box::new(CountingResolver {
+            len: size_of::<RknpuTask>(),
+            lookups: AtomicU32::new(0),
+        }));
+        dev.set_buffer_resolver(resolver);
+        let (mut submit, task_kva) = completing_submit(&dev, npu, core);
+        submit.task_obj_addr = task_kva.as_usize() as u64;
+        (dev, npu, resolver, submit)
+    }
+
+    #[test]
+    fn unchecked_submit_skips_validation() {
+        let (dev, npu, resolver, mut submit) = resolved_submit(NpuCore::Npu0);
+        assert_eq!(dev.rknpu_submit_ioctl(&mut submit), Ok(()));
+        assert_eq!(resolver.lookups.load(Ordering::Relaxed), 1);
+
+        npu.arrive_on_start(
+            reg(NpuCore::Npu0, npu_reg::PC_OP_EN),
+            dev.config.int_done_mask,
+        );
+        assert_eq!(unsafe { dev.rknpu_submit_unchecked(&mut submit) }, Ok(()));
+        assert_eq!(resolver.lookups.load(Ordering::Relaxed), 1);
+    }
+
+    /// 测量免校验提交省下的耗时，即安全入口多做的标志解析、任务数和任务范围检查：
+    /// `cargo test --release -- --ignored --nocapture unchecked_submit_latency`
+    ///
+    /// 整个提交的耗时由轮询等待主导，两种入口直接对比差异淹没在噪声里，
+    /// 这里只对被跳过的步骤单独计时。
+    #[test]
+    #[ignore = "耗时测量，按需手动运行"]
+    fn unchecked_submit_latency() {
+        const ROUNDS: u32 = 1_000_000;
+        let (dev, _, _, submit) = resolved_submit(NpuCore::Npu0);
+
+        let start = std::time::Instant::now();
+        for _ in 0..ROUNDS {
+            let submit = std::hint::black_box(&submit);
+            std::hint::black_box(dev.validate_submit(submit).unwrap());
+            dev.check_task_range(submit).unwrap();
+        }
+        std::println!(
+            "validation skipped by rknpu_submit_unchecked: {:?}/submit",
+            start.elapsed() / ROUNDS
+        );
+    }
+
+    std::thread_local! {
+        /// 当前线程发出的 debug 及更详细级别的日志条数
+        static DEBUG_RECORDS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+    }
+
+    /// 按线程统计日志条数，测试并行运行时互不干扰
+    struct CountingLogger;
+
+    impl log::Log for CountingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            if record.level() >= log::Level::Debug {
+                DEBUG_RECORDS.with(|n| n.set(n.get() + 1));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn submit_fast_path_formats_no_debug_logs_without_verbose_trace() {
+        static LOGGER: CountingLogger = CountingLogger;
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+        // 日志级别全开，任何 debug! 都会格式化参数并被计数
+        DEBUG_RECORDS.with(|n| n.set(0));
+        debug!("[RKNPU] probe");
+        assert_eq!(DEBUG_RECORDS.with(|n| n.get()), 1);
+
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        DEBUG_RECORDS.with(|n| n.set(0));
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        let records = DEBUG_RECORDS.with(|n| n.get());
+        if cfg!(feature = "verbose-trace") {
+            assert!(records > 0);
+        } else {
+            assert_eq!(records, 0);
+        }
+    }
+
+    #[test]
+    fn initialize_with_mock_registers() {
+        let (dev, _, _) = initialized_dev(RkBoard::Rk3588);
+        assert!(dev.initialized);
+    }
+
+    #[test]
+    fn initialize_clears_interrupts_on_every_core() {
+        use crate::configs::INT_CLEAR_VALUE;
+
+        for board in RkBoard::ALL {
+            let (mut dev, npu, _) = mock_dev(board);
+            let cores: Vec<_> = dev.available_cores().collect();
+            for &core in &cores {
+                npu.set(reg(core, npu_reg::VERSION), dev.config.expected_version);
+            }
+            dev.initialize().unwrap();
+            for core in [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2] {
+                let expected = if cores.contains(&core) {
+                    vec![INT_CLEAR_VALUE]
+                } else {
+                    vec![]
+                };
+                assert_eq!(
+                    npu.writes_to(reg(core, npu_reg::INT_CLEAR)),
+                    expected,
+                    "{:?} {:?}",
+                    board,
+                    core
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn initialize_shutdown_initialize_cycle() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let all_on = PowerState {
+            npu: true,
+            nputop: true,
+            npu1: true,
+            npu2: true,
+        };
+        assert_eq!(dev.power_state(), all_on);
+
+        dev.shutdown().unwrap();
+        assert!(!dev.initialized);
+        assert_eq!(dev.power_state(), PowerState::default());
+        for core in [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2] {
+            assert_eq!(npu.writes_to(reg(core, npu_reg::INT_MASK)), vec![0]);
+        }
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::NotInitialized)
+        );
+
+        // 再次初始化重新上电并重新校验版本
+        let version = reg(NpuCore::Npu0, npu_reg::VERSION);
+        let version_reads = npu.reads_of(version);
+        dev.initialize().unwrap();
+        assert!(dev.initialized);
+        assert_eq!(dev.power_state(), all_on);
+        assert!(npu.reads_of(version) > version_reads);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+    }
+
+    /// 提交到 `core` 的单任务 PC 提交，DMA 写错误中断在启动任务后到达
+    fn failing_submit(dev: &RknpuDev, npu: &MockMmio, core: NpuCore) -> (RknpuSubmit, VirtAddr) {
+        let (submit, task_kva) = completing_submit(dev, npu, core);
+        npu.arrive_on_start(reg(core, npu_reg::PC_OP_EN), IntStatus::DMA_WRITE_ERROR);
+        (submit, task_kva)
+    }
+
+    #[test]
+    fn consecutive_hardware_errors_latch_failed_state() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let dev = dev.with_fault_threshold(2);
+
+        let (mut submit, task_kva) = failing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::HardwareError)
+        );
+        // 成功的提交清零计数
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        let (mut submit, task_kva) = failing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::HardwareError)
+        );
+        assert!(!dev.is_failed());
+
+        let (mut submit, task_kva) = failing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::HardwareError)
+        );
+        assert!(dev.is_failed());
+
+        // 锁定后不再访问硬件
+        npu.clear_writes();
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::DeviceFailed)
+        );
+        assert!(npu.writes().is_empty());
+    }
+
+    #[test]
+    fn clear_fault_allows_submits_again() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let mut dev = dev.with_fault_threshold(1);
+        let (mut submit, task_kva) = failing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::HardwareError)
+        );
+        assert!(dev.is_failed());
+
+        // 复位后核心仍不响应时保持故障状态
+        let version = reg(NpuCore::Npu0, npu_reg::VERSION);
+        npu.set(version, 0);
+        assert_eq!(dev.clear_fault(), Err(RkNpuError::HardwareError));
+        assert!(dev.is_failed());
+
+        npu.set(version, dev.config.expected_version);
+        dev.clear_fault().unwrap();
+        assert!(!dev.is_failed());
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+    }
+
+    #[test]
+    fn interrupt_storm_masks_the_stuck_interrupt() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let int_mask = reg(NpuCore::Npu0, npu_reg::INT_MASK);
+        npu.set(int_mask, 0x1ffff);
+        // 写 int_clear 无效，中断一直挂起
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_STATUS), IntStatus::DPU);
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_RAW_STATUS), IntStatus::DPU);
+        npu.stick(reg(NpuCore::Npu0, npu_reg::INT_CLEAR));
+
+        for _ in 0..IRQ_STORM_THRESHOLD {
+            assert_eq!(dev.handle_irq(NpuCore::Npu0), Ok(IntStatus::DPU));
+        }
+        assert!(npu.writes_to(int_mask).is_empty());
+
+        assert_eq!(
+            dev.handle_irq(NpuCore::Npu0),
+            Err(RkNpuError::InterruptStorm)
+        );
+        assert_eq!(npu.writes_to(int_mask), vec![0x1ffff & !IntStatus::DPU]);
+    }
+
+    #[test]
+    fn inactive_core_excluded_from_selection_and_explicit_submits() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        assert_eq!(
+            dev.set_active_core_mask(NpuCore::Npu0.mask_bit() | 0x8),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(dev.set_active_core_mask(0), Err(RkNpuError::InvalidInput));
+        dev.set_active_core_mask(NpuCore::Npu1.mask_bit() | NpuCore::Npu2.mask_bit())
+            .unwrap();
+
+        // NPU0 最空闲，但已被屏蔽
+        dev.jobs.begin(NpuCore::Npu1, None).unwrap();
+        dev.jobs.begin(NpuCore::Npu2, None).unwrap();
+        for priority in [
+            SubmitPriority::Low,
+            SubmitPriority::Normal,
+            SubmitPriority::High,
+        ] {
+            assert_ne!(dev.select_core(0, priority, 0), Ok(NpuCore::Npu0));
+        }
+
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        submit.task_obj_addr = task_kva.as_usize() as u64;
+        npu.clear_writes();
+        assert_eq!(
+            dev.submit_on(NpuCore::Npu0, &mut submit),
+            Err(RkNpuError::CoreUnavailable)
+        );
+        submit.core_mask = NpuCore::Npu0.mask_bit() | NpuCore::Npu1.mask_bit();
+        assert_eq!(
+            dev.rknpu_submit_ioctl(&mut submit),
+            Err(RkNpuError::CoreUnavailable)
+        );
+        assert!(npu.writes().is_empty());
+    }
+
+    #[test]
+    fn submit_on_missing_core_is_invalid() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3568);
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        submit.task_obj_addr = task_kva.as_usize() as u64;
+        npu.clear_writes();
+        assert_eq!(
+            dev.submit_on(NpuCore::Npu2, &mut submit),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(submit.core_mask, NpuCore::Npu0.mask_bit());
+        assert!(npu.writes().is_empty());
+        assert_eq!(dev.submit_on(NpuCore::Npu0, &mut submit), Ok(()));
+    }
+
+    #[test]
+    fn npu2_access_on_single_core_board_errors() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3568);
+        let core = NpuCore::Npu2;
+        assert_eq!(dev.core_version(core), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.int_status(core).err(), Some(RkNpuError::InvalidInput));
+        assert_eq!(dev.handle_irq(core), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.wait_int(core, 0x1, 10), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.set_int_mask(core, 0x1), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.clear_int(core, 0x1), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.clear_rw_amount(core), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.power_on_core(core), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.abort_job(core), Err(RkNpuError::InvalidInput));
+        assert_eq!(dev.read_perf_counter(core), None);
+        // 没有访问 NPU2 所在的寄存器窗口
+        assert!(npu.writes().is_empty());
+        for offset in [npu_reg::VERSION, npu_reg::INT_STATUS] {
+            assert_eq!(npu.reads_of(reg(core, offset)), 0);
+        }
+    }
+
+    #[test]
+    fn per_task_int_masks_programmed_as_tasks_advance() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let done = dev.config.int_done_mask;
+        let with_ints = |int_mask, int_clear| RknpuTask {
+            int_mask,
+            int_clear,
+            ..task(16, 0x3000_0000)
+        };
+        let tasks = [
+            with_ints(IntStatus::CNA_FEATURE, 0x1ffff),
+            with_ints(IntStatus::CNA_WEIGHT, IntStatus::CNA_FEATURE),
+            with_ints(done, IntStatus::CNA_WEIGHT),
+        ];
+        let submit = RknpuSubmit {
+            task_number: 3,
+            ..Default::default()
+        };
+        assert!(RknpuDev::has_per_task_ints(tasks.as_ptr(), &submit));
+        let same = [tasks[2]; 3];
+        assert!(!RknpuDev::has_per_task_ints(same.as_ptr(), &submit));
+
+        let pc_task_status = reg(NpuCore::Npu0, dev.config.pc_task_status_offset as usize);
+        npu.script(pc_task_status, &[0, 1, 1, 2, 3]);
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_RAW_STATUS), done);
+        assert_eq!(
+            dev.wait_job_done_per_task(NpuCore::Npu0, 1000, tasks.as_ptr(), 0, 3),
+            Ok(done)
+        );
+        // 第一个任务的值在提交时写入，之后每推进一个任务写一次
+        assert_eq!(
+            npu.writes_to(reg(NpuCore::Npu0, npu_reg::INT_MASK)),
+            vec![IntStatus::CNA_WEIGHT, done]
+        );
+        assert_eq!(
+            npu.writes_to(reg(NpuCore::Npu0, npu_reg::INT_CLEAR)),
+            vec![IntStatus::CNA_FEATURE, IntStatus::CNA_WEIGHT]
+        );
+    }
+
+    #[test]
+    fn classify_version_readings() {
+        let (dev, _, _) = mock_dev(RkBoard::Rk3588);
+        let expected = dev.config.expected_version;
+        assert_eq!(dev.classify_version(expected), Ok(()));
+        assert_eq!(
+            dev.classify_version(0),
+            Err(RkNpuError::DeviceNotResponding)
+        );
+        assert_eq!(
+            dev.classify_version(u32::MAX),
+            Err(RkNpuError::DeviceNotResponding)
+        );
+        assert_eq!(
+            dev.classify_version(expected ^ 0x1),
+            Err(RkNpuError::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn initialize_prefers_not_responding_over_version_mismatch() {
+        let (mut dev, npu, _) = mock_dev(RkBoard::Rk3588);
+        let expected = dev.config.expected_version;
+        npu.set(reg(NpuCore::Npu0, npu_reg::VERSION), expected ^ 0x1);
+        npu.set(reg(NpuCore::Npu1, npu_reg::VERSION), 0);
+        npu.set(reg(NpuCore::Npu2, npu_reg::VERSION), expected);
+        assert_eq!(dev.initialize(), Err(RkNpuError::DeviceNotResponding));
+    }
+
+    #[test]
+    fn try_new_rejects_aliased_bases() {
+        let try_new = |base, cru_base, pm_base| {
+            RknpuDev::try_new(base, cru_base, pm_base, RkBoard::Rk3588, identity).err()
+        };
+        assert_eq!(try_new(0x1000_0000, 0x2000_0000, 0x3000_0000), None);
+        // 复制粘贴导致的相同基地址
+        assert_eq!(
+            try_new(0x1000_0000, 0x1000_0000, 0x3000_0000),
+            Some(RkNpuError::InvalidInput)
+        );
+        assert_eq!(
+            try_new(0x1000_0000, 0x2000_0000, 0x2000_0000),
+            Some(RkNpuError::InvalidInput)
+        );
+        // CRU 落在最后一个核心的寄存器窗口内
+        assert_eq!(
+            try_new(0x1000_0000, 0x1000_0000 + 2 * NPU_CORE_SIZE, 0x3000_0000),
+            Some(RkNpuError::InvalidInput)
+        );
+        // 单核板型只占一个核心窗口
+        assert!(
+            RknpuDev::try_new(
+                0x1000_0000,
+                0x1000_0000 + NPU_CORE_SIZE,
+                0x3000_0000,
+                RkBoard::Rk3568,
+                identity
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn submit_before_initialize_errors() {
+        let (dev, npu, cru) = mock_dev(RkBoard::Rk3588);
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        submit.task_obj_addr = task_kva.as_usize() as u64;
+        assert_eq!(
+            dev.rknpu_submit_ioctl(&mut submit),
+            Err(RkNpuError::NotInitialized)
+        );
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::NotInitialized)
+        );
+        let mut action = RknpuAction {
+            flags: RknpuActionFlag::GetHwVersion as u32,
+            value: 0,
+        };
+        assert_eq!(
+            dev.rknpu_action_ioctl(&mut action),
+            Err(RkNpuError::NotInitialized)
+        );
+        // 未上电的寄存器一次也没有访问
+        assert!(npu.writes().is_empty());
+        assert!(cru.writes().is_empty());
+        assert_eq!(npu.reads_of(reg(NpuCore::Npu0, npu_reg::VERSION)), 0);
+        assert_eq!(npu.reads_of(reg(NpuCore::Npu0, npu_reg::INT_STATUS)), 0);
+    }
+
+    #[test]
+    fn submit_to_powered_off_core() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.power_off_core(NpuCore::Npu2).unwrap();
+        npu.clear_writes();
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu2);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::DomainNotFound)
+        );
+        assert!(npu.writes().is_empty());
+        assert_eq!(dev.current_job(NpuCore::Npu2), None);
+
+        // 开启 auto_power 后先上电再提交
+        let dev = dev.with_auto_power(true);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        assert!(dev.is_core_powered(NpuCore::Npu2));
+    }
+
+    #[test]
+    fn submit_beyond_max_inflight_is_busy() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let fences = RecordingFences::leak();
+        dev.set_fence_provider(fences);
+        // 其他提交者登记、尚未完成的任务占满任务表
+        let held: Vec<_> = (0..MAX_INFLIGHT)
+            .map(|_| dev.jobs.begin(NpuCore::Npu1, None).unwrap())
+            .collect();
+
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        submit.flags |= SubmitFlags::FENCE_OUT;
+        npu.clear_writes();
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::Busy)
+        );
+        assert!(npu.writes().is_empty());
+        assert_eq!(fences.signaled(), vec![(FenceId(1), Err(RkNpuError::Busy))]);
+        // 已有的在途任务没有被覆盖
+        assert_eq!(dev.pending_jobs(), MAX_INFLIGHT);
+        assert_eq!(dev.current_job(NpuCore::Npu1), Some(held[0]));
+
+        // 释放一个槽位后可以继续提交
+        dev.jobs.finish(NpuCore::Npu1).unwrap();
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        assert_eq!(dev.pending_jobs(), MAX_INFLIGHT - 1);
+    }
+
+    #[test]
+    fn timed_out_job_slot_is_reclaimed() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        // 完成中断始终不到达
+        npu.arrive_on_start(reg(NpuCore::Npu0, npu_reg::PC_OP_EN), 0);
+        submit.timeout = 1;
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::TaskTimeout)
+        );
+        assert_eq!(dev.pending_jobs(), 0);
+        assert_eq!(dev.current_job(NpuCore::Npu0), None);
+
+        // 占满其余槽位，下一个提交只能复用超时任务释放的槽位
+        for _ in 1..MAX_INFLIGHT {
+            dev.jobs.begin(NpuCore::Npu1, None).unwrap();
+        }
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu0);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+    }
+
+    #[test]
+    fn high_priority_submit_picks_the_idle_core() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.jobs.begin(NpuCore::Npu0, None).unwrap();
+        dev.jobs.begin(NpuCore::Npu2, None).unwrap();
+        dev.jobs.begin(NpuCore::Npu2, None).unwrap();
+
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu1);
+        submit.core_mask = 0;
+        // 高优先级
+        submit.flags |= 2 << 28;
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        assert_eq!(
+            npu.writes_to(reg(NpuCore::Npu1, npu_reg::PC_OP_EN)),
+            vec![0x1, 0x0]
+        );
+    }
+
+    #[test]
+    fn enable_mask_set_before_pc_op_and_restored_after() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let core = NpuCore::Npu0;
+        let enable_mask = reg(core, npu_reg::ENABLE_MASK);
+        let op_en = reg(core, npu_reg::PC_OP_EN);
+        npu.set(enable_mask, 0x3);
+        npu.clear_writes();
+
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([RknpuTask {
+            int_clear: crate::configs::INT_CLEAR_VALUE,
+            enable_mask: 0x15,
+            ..task(16, 0x3000_0000)
+        }]));
+        npu.arrive_on_start(reg(core, npu_reg::PC_OP_EN), dev.config.int_done_mask);
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC | SubmitFlags::ENABLE_MASK,
+            task_number: 1,
+            core_mask: core.mask_bit(),
+            ..Default::default()
+        };
+        let task_kva = VirtAddr::from(tasks.as_ptr() as usize);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+
+        let order: Vec<_> = npu
+            .writes()
+            .into_iter()
+            .filter(|&(o, _)| o == enable_mask || o == op_en)
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (enable_mask, 0x15),
+                (op_en, 0x1),
+                (op_en, 0x0),
+                (enable_mask, 0x3)
+            ]
+        );
+        assert_eq!(npu.get(enable_mask), 0x3);
+
+        // 超出允许范围的位在触碰硬件之前被拒绝
+        npu.clear_writes();
+        tasks[0].enable_mask = dev.config.enable_mask_allowed + 1;
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert!(npu.writes_to(enable_mask).is_empty());
+        assert!(npu.writes_to(op_en).is_empty());
+    }
+
+    #[test]
+    fn regcmd_addr_must_fit_32_bit_register() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        assert_eq!(dev.config.dma_mask_bits, 40);
+        let core = NpuCore::Npu0;
+        let pc_data_addr = reg(core, npu_reg::PC_DATA_ADDR);
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([RknpuTask {
+            int_clear: crate::configs::INT_CLEAR_VALUE,
+            ..task(16, 0xffff_f000)
+        }]));
+        let task_kva = VirtAddr::from(tasks.as_ptr() as usize);
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_number: 1,
+            core_mask: core.mask_bit(),
+            ..Default::default()
+        };
+
+        // 4GB 以下原样写入
+        npu.arrive_on_start(reg(core, npu_reg::PC_OP_EN), dev.config.int_done_mask);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        assert_eq!(
+            npu.writes_to(pc_data_addr),
+            vec![dev.config.slave_mode_value, 0xffff_f000]
+        );
+
+        // 在 40 位 DMA 范围内但高于 4GB，不能截断写入
+        for addr in [0x1_0000_0000, 0xff_ffff_f000, 0x100_0000_0000] {
+            npu.clear_writes();
+            tasks[0].regcmd_addr = addr;
+            assert_eq!(
+                dev.rknpu_submit_kva(&mut submit, task_kva),
+                Err(RkNpuError::InvalidInput)
+            );
+            assert!(npu.writes_to(pc_data_addr).is_empty());
+            assert!(npu.writes_to(reg(core, npu_reg::PC_OP_EN)).is_empty());
+        }
+    }
+
+    #[test]
+    fn pc_dma_base_must_fit_32_bit_register() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3562);
+        let base = reg(NpuCore::Npu0, npu_reg::PC_DMA_BASE_ADDR);
+        assert_eq!(dev.set_pc_dma_base(NpuCore::Npu0, 0xffff_f000), Ok(()));
+        assert_eq!(npu.writes_to(base), vec![0xffff_f000]);
+
+        npu.clear_writes();
+        assert_eq!(
+            dev.set_pc_dma_base(NpuCore::Npu0, 0x1_0000_0000),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert!(npu.writes().is_empty());
+    }
+
+    #[test]
+    fn every_task_regcmd_addr_checked_against_dma_mask() {
+        for board in RkBoard::ALL {
+            let (dev, npu, _) = initialized_dev(board);
+            let limit = 1u64 << dev.config.dma_mask_bits;
+            // 只有首个任务的地址写入 32 位寄存器，其余任务由 PC 引擎按 DMA 位宽读取
+            let tasks = std::boxed::Box::leak(std::boxed::Box::new([
+                task(16, 0x3000_0000),
+                task(16, limit - 0x1000),
+            ]));
+            let task_kva = VirtAddr::from(tasks.as_ptr() as usize);
+            let mut submit = RknpuSubmit {
+                flags: SubmitFlags::PC | SubmitFlags::DRY_RUN,
+                task_number: 2,
+                ..Default::default()
+            };
+            assert_eq!(
+                dev.rknpu_submit_kva(&mut submit, task_kva),
+                Ok(()),
+                "{:?}",
+                board
+            );
+
+            tasks[1].regcmd_addr = limit;
+            assert_eq!(
+                dev.rknpu_submit_kva(&mut submit, task_kva),
+                Err(RkNpuError::InvalidInput),
+                "{:?}",
+                board
+            );
+            assert!(npu.writes().is_empty(), "{:?}", board);
+        }
+    }
+
+    #[test]
+    fn completion_clear_follows_strategy_on_wait_and_irq_paths() {
+        use crate::configs::IntClearStrategy;
+
+        for board in RkBoard::ALL {
+            let default = RknpuConfig::from_board(board).int_clear_strategy;
+            for strategy in [default, IntClearStrategy::WriteAll] {
+                let (dev, npu, _) = initialized_dev(board);
+                let dev = dev.with_int_clear_strategy(strategy);
+                let core = NpuCore::Npu0;
+                let done = dev.config.int_done_mask;
+                let int_clear = reg(core, npu_reg::INT_CLEAR);
+
+                // 等待路径：最后一次 int_clear 写入是完成清除
+                let (mut submit, task_kva) = completing_submit(&dev, npu, core);
+                assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+                assert_eq!(
+                    npu.writes_to(int_clear).last(),
+                    Some(&strategy.clear_value(done)),
+                    "{:?} {:?}",
+                    board,
+                    strategy
+                );
+
+                // 中断路径
+                npu.clear_writes();
+                npu.set(reg(core, npu_reg::INT_STATUS), done);
+                assert_eq!(dev.handle_irq(core), Ok(done));
+                assert_eq!(
+                    npu.writes_to(int_clear),
+                    vec![strategy.clear_value(done)],
+                    "{:?} {:?}",
+                    board,
+                    strategy
+                );
+                assert_eq!(npu.get(reg(core, npu_reg::INT_STATUS)), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn done_detection_uses_each_boards_done_mask() {
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+        for board in RkBoard::ALL {
+            let (dev, npu, _) = initialized_dev(board);
+            let done = dev.config.int_done_mask;
+            // 任一完成位都结束等待
+            for bit in (0..32).map(|i| 1u32 << i).filter(|bit| done & bit != 0) {
+                npu.set(status, bit);
+                assert_eq!(
+                    dev.wait_job_done(NpuCore::Npu0, 1000, 1, None, None, (0, 0)),
+                    Ok(None),
+                    "{:?} 0x{:x}",
+                    board,
+                    bit
+                );
+            }
+
+            // 非完成位不结束等待
+            let other = IntStatus::KNOWN_MASK & !IntStatus::ERROR_MASK & !done;
+            npu.set(status, other);
+            assert_eq!(
+                dev.wait_job_done(NpuCore::Npu0, 1, 1, None, None, (0, 0)),
+                Err(RkNpuError::TaskTimeout),
+                "{:?}",
+                board
+            );
+        }
+
+        // 完成位不同的板型只认自己的完成位
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.config.int_done_mask = IntStatus::PPU;
+        npu.set(status, IntStatus::DPU);
+        assert_eq!(
+            dev.wait_job_done(NpuCore::Npu0, 1, 1, None, None, (0, 0)),
+            Err(RkNpuError::TaskTimeout)
+        );
+        npu.set(status, IntStatus::PPU);
+        assert_eq!(
+            dev.wait_job_done(NpuCore::Npu0, 1000, 1, None, None, (0, 0)),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn initialize_reads_version_and_version_num() {
+        let (mut dev, npu, _) = mock_dev(RkBoard::Rk3588);
+        dev.config.expected_version_num = Some(0x10);
+        for core in [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2] {
+            npu.set(reg(core, npu_reg::VERSION), dev.config.expected_version);
+            npu.set(reg(core, npu_reg::VERSION_NUM), 0x10);
+        }
+        // 新的小版本只告警，不影响初始化
+        npu.set(reg(NpuCore::Npu1, npu_reg::VERSION_NUM), 0x11);
+
+        assert_eq!(dev.initialize(), Ok(()));
+        for core in [NpuCore::Npu0, NpuCore::Npu1, NpuCore::Npu2] {
+            assert!(npu.reads_of(reg(core, npu_reg::VERSION)) > 0, "{:?}", core);
+            assert!(
+                npu.reads_of(reg(core, npu_reg::VERSION_NUM)) > 0,
+                "{:?}",
+                core
+            );
+        }
+    }
+
+    #[test]
+    fn power_state_tracks_init_and_shutdown() {
+        for board in RkBoard::ALL {
+            let (mut dev, npu, _) = mock_dev(board);
+            for core in dev.available_cores().collect::<Vec<_>>() {
+                npu.set(reg(core, npu_reg::VERSION), dev.config.expected_version);
+            }
+            assert!(dev.power_state().is_all_off(), "{:?}", board);
+
+            dev.initialize().unwrap();
+            assert_eq!(
+                dev.power_state(),
+                PowerState {
+                    npu: true,
+                    nputop: true,
+                    npu1: true,
+                    npu2: true,
+                },
+                "{:?}",
+                board
+            );
+
+            if dev.config.is_available(NpuCore::Npu1) {
+                dev.power_off_core(NpuCore::Npu1).unwrap();
+                assert!(!dev.power_state().npu1, "{:?}", board);
+                assert!(dev.power_state().nputop, "{:?}", board);
+            }
+
+            dev.shutdown().unwrap();
+            assert!(dev.power_state().is_all_off(), "{:?}", board);
+        }
+    }
+
+    #[test]
+    fn bus_reset_toggles_each_available_cores_srst_bits() {
+        // (板型, AXI 复位位, AHB 复位位)
+        let cases = [
+            (RkBoard::Rk3588, 0b01_0101, 0b10_1010),
+            (RkBoard::Rk3583, 0b00_0101, 0b00_1010),
+            (RkBoard::Rk3568, 0b00_0001, 0b00_0010),
+            (RkBoard::Rv1106, 0b00_0001, 0b00_0010),
+            (RkBoard::Rk3562, 0b00_0001, 0b00_0010),
+        ];
+        for (board, axi, ahb) in cases {
+            let (dev, _, cru) = initialized_dev(board);
+            dev.bus_reset().unwrap();
+            assert_eq!(
+                cru.writes_to(cru_reg::SOFTRST_CON_NPU),
+                vec![axi << 16 | axi, axi << 16, ahb << 16 | ahb, ahb << 16],
+                "{:?}",
+                board
+            );
+        }
+    }
+
+    #[test]
+    fn read_npu_clk_hz_decodes_cru_dividers() {
+        let (dev, _, cru) = initialized_dev(RkBoard::Rk3588);
+        let clksel = |mux: u32, div: u32| (mux << 7) | (div << 2);
+
+        // gpll 1188MHz / 2
+        cru.set(cru_reg::CLKSEL_CON_NPU, clksel(0, 1));
+        assert_eq!(dev.read_npu_clk_hz(), Ok(594_000_000));
+        // cpll 1500MHz / 3
+        cru.set(cru_reg::CLKSEL_CON_NPU, clksel(1, 2));
+        assert_eq!(dev.read_npu_clk_hz(), Ok(500_000_000));
+
+        // npll：24MHz * 200 / (3 * 2^1) = 800MHz，再二分频
+        cru.set(cru_reg::NPLL_CON0, 200);
+        cru.set(cru_reg::NPLL_CON1, (1 << 6) | 3);
+        cru.set(cru_reg::CLKSEL_CON_NPU, clksel(3, 1));
+        assert_eq!(dev.read_npu_clk_hz(), Ok(400_000_000));
+
+        let mut action = RknpuAction {
+            flags: RknpuActionFlag::GetFreq as u32,
+            value: 0,
+        };
+        dev.rknpu_action_ioctl(&mut action).unwrap();
+        assert_eq!(action.value, 400_000_000);
+
+        // 未定义的父时钟
+        cru.set(cru_reg::CLKSEL_CON_NPU, clksel(7, 0));
+        assert_eq!(dev.read_npu_clk_hz(), Err(RkNpuError::HardwareError));
+        assert!(cru.writes().is_empty());
+    }
+
+    #[test]
+    fn overflowing_task_ranges_are_invalid_input() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([task(16, 0x3000_0000)]));
+        for (task_start, task_number) in [
+            (u32::MAX, 1),
+            (u32::MAX - 1, 2),
+            (1, u32::MAX),
+            (0, u32::MAX),
+            (u32::MAX, u32::MAX),
+        ] {
+            let mut submit = RknpuSubmit {
+                flags: SubmitFlags::PC,
+                task_start,
+                task_number,
+                task_obj_addr: tasks.as_ptr() as u64,
+                core_mask: NpuCore::Npu0.mask_bit(),
+                ..Default::default()
+            };
+            assert_eq!(
+                dev.rknpu_submit_ioctl(&mut submit),
+                Err(RkNpuError::InvalidInput),
+                "{} {}",
+                task_start,
+                task_number
+            );
+            // 范围计算溢出时在读取任务描述符之前返回
+            if task_start.checked_add(task_number).is_none() {
+                let flags = SubmitFlags::parse(submit.flags).unwrap();
+                assert!(matches!(
+                    dev.pc_job_regs(tasks.as_ptr(), &submit, flags),
+                    Err(RkNpuError::InvalidInput)
+                ));
+            }
+        }
+
+        // regcfg_amount 加上额外配置数后溢出
+        tasks[0] = task(u32::MAX, 0x3000_0000);
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_number: 1,
+            task_obj_addr: tasks.as_ptr() as u64,
+            core_mask: NpuCore::Npu0.mask_bit(),
+            ..Default::default()
+        };
+        assert_eq!(
+            dev.rknpu_submit_ioctl(&mut submit),
+            Err(RkNpuError::InvalidInput)
+        );
+
+        assert!(npu.writes().is_empty());
+        assert_eq!(dev.pending_jobs(), 0);
+    }
+
+    #[test]
+    fn pending_job_counts_follow_submit_and_completion() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        assert_eq!(dev.pending_jobs(), 0);
+
+        // 已编程到硬件、等待中断完成的任务
+        dev.jobs.begin(NpuCore::Npu0, None).unwrap();
+        dev.jobs.begin(NpuCore::Npu2, None).unwrap();
+        assert_eq!(dev.pending_jobs(), 2);
+        assert_eq!(dev.pending_jobs_on(NpuCore::Npu0), 1);
+        assert_eq!(dev.pending_jobs_on(NpuCore::Npu1), 0);
+        assert_eq!(dev.pending_jobs_on(NpuCore::Npu2), 1);
+
+        let done = dev.config.int_done_mask;
+        npu.set(reg(NpuCore::Npu0, npu_reg::INT_STATUS), done);
+        assert_eq!(dev.handle_irq(NpuCore::Npu0), Ok(done));
+        assert_eq!(dev.pending_jobs(), 1);
+        assert_eq!(dev.pending_jobs_on(NpuCore::Npu0), 0);
+
+        // 同步提交完成或失败后都不留下计数
+        let (mut submit, task_kva) = completing_submit(&dev, npu, NpuCore::Npu1);
+        assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+        let (mut submit, task_kva) = failing_submit(&dev, npu, NpuCore::Npu1);
+        assert_eq!(
+            dev.rknpu_submit_kva(&mut submit, task_kva),
+            Err(RkNpuError::HardwareError)
+        );
+        assert_eq!(dev.pending_jobs_on(NpuCore::Npu1), 0);
+        assert_eq!(dev.pending_jobs(), 1);
+
+        npu.set(reg(NpuCore::Npu2, npu_reg::INT_STATUS), done);
+        assert_eq!(dev.handle_irq(NpuCore::Npu2), Ok(done));
+        assert_eq!(dev.pending_jobs(), 0);
+    }
+
+    #[test]
+    fn wait_int_follows_scripted_status_transitions() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+        let done = dev.config.int_done_mask;
+
+        // 中间中断位不结束等待，返回值含全部观察到的位
+        npu.script(
+            status,
+            &[0, IntStatus::CNA_FEATURE, IntStatus::CNA_FEATURE | done],
+        );
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, 1000),
+            Ok(IntStatus::CNA_FEATURE | done)
+        );
+        assert_eq!(npu.reads_of(status), 3);
+        // 不清除中断
+        assert!(npu.writes().is_empty());
+
+        // 等待完成以外的条件
+        npu.script(status, &[done, done | IntStatus::PPU]);
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, IntStatus::PPU, 1000),
+            Ok(done | IntStatus::PPU)
+        );
+
+        npu.script(status, &[0, IntStatus::DMA_READ_ERROR]);
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, 1000),
+            Err(RkNpuError::HardwareError)
+        );
+
+        npu.script(status, &[0, IntStatus::CNA_FEATURE]);
+        assert_eq!(
+            dev.wait_int(NpuCore::Npu0, done, 1),
+            Err(RkNpuError::Timeout)
+        );
+    }
+
+    std::thread_local! {
+        static TRANSLATIONS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+    }
+
+    /// 按线程统计调用次数的恒等地址转换
+    fn counting_identity(addr: PhysAddr) -> VirtAddr {
+        TRANSLATIONS.with(|n| n.set(n.get() + 1));
+        identity(addr)
+    }
+
+    #[test]
+    fn coherent_submit_skips_command_buffer_flushes_only_when_allowed() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.dma_to_kernel = counting_identity;
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([
+            task(16, 0x3000_0000),
+            task(16, 0x3001_0000),
+            task(16, 0x3002_0000),
+        ]));
+        let task_kva = VirtAddr::from(tasks.as_ptr() as usize);
+        let submit_with = |dev: &RknpuDev, flags| {
+            npu.arrive_on_start(
+                reg(NpuCore::Npu0, npu_reg::PC_OP_EN),
+                dev.config.int_done_mask,
+            );
+            let mut submit = RknpuSubmit {
+                flags,
+                task_number: 3,
+                core_mask: NpuCore::Npu0.mask_bit(),
+                ..Default::default()
+            };
+            TRANSLATIONS.with(|n| n.set(0));
+            assert_eq!(dev.rknpu_submit_kva(&mut submit, task_kva), Ok(()));
+            TRANSLATIONS.with(|n| n.get())
+        };
+
+        // 每个任务的命令缓冲区各刷新一次
+        assert_eq!(submit_with(&dev, SubmitFlags::PC), 3);
+        // 未授权时忽略 COHERENT
+        assert_eq!(
+            submit_with(&dev, SubmitFlags::PC | SubmitFlags::COHERENT),
+            3
+        );
+
+        let dev = dev.with_coherent_submit(true);
+        assert_eq!(
+            submit_with(&dev, SubmitFlags::PC | SubmitFlags::COHERENT),
+            0
+        );
+        assert_eq!(submit_with(&dev, SubmitFlags::PC), 3);
+    }
+
+    /// 每次读取前进 `step` 微秒的时钟，模拟每次轮询耗时固定的慢设备
+    struct SteppingClock {
+        now: AtomicU64,
+        step: u64,
+    }
+
+    impl Clock for SteppingClock {
+        fn now_us(&self) -> u64 {
+            self.now.fetch_add(self.step, Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn wait_times_out_by_elapsed_time_not_poll_count() {
+        let status = reg(NpuCore::Npu0, npu_reg::INT_STATUS);
+        for step in [10, 100, 1_000] {
+            let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+            let clock: &'static SteppingClock =
+                std::boxed::Box::leak(std::boxed::Box::new(SteppingClock {
+                    now: AtomicU64::new(0),
+                    step,
+                }));
+            dev.set_clock(clock);
+            let done = dev.config.int_done_mask;
+
+            assert_eq!(
+                dev.wait_int(NpuCore::Npu0, done, 5),
+                Err(RkNpuError::Timeout)
+            );
+            // 到期的那次检查时读数刚好达到 5ms
+            let elapsed = clock.now.load(Ordering::Relaxed) - step;
+            assert!((5_000..5_000 + step).contains(&elapsed), "{}", elapsed);
+            // 轮询次数随每次轮询的耗时变化，总时长不变；到期的那次检查不再读取状态
+            assert_eq!(npu.reads_of(status) as u64, 5_000 / step - 1, "{}", step);
+        }
+    }
+
+    #[test]
+    fn submit_path_translates_through_the_installed_translator() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        dev.dma_to_kernel = counting_identity;
+        let tasks = std::boxed::Box::leak(std::boxed::Box::new([
+            task(16, 0x3000_0000),
+            task(16, 0x3001_0000),
+        ]));
+        npu.arrive_on_start(
+            reg(NpuCore::Npu0, npu_reg::PC_OP_EN),
+            dev.config.int_done_mask,
+        );
+        let mut submit = RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_number: 2,
+            task_obj_addr: tasks.as_ptr() as u64,
+            core_mask: NpuCore::Npu0.mask_bit(),
+            ..Default::default()
+        };
+
+        TRANSLATIONS.with(|n| n.set(0));
+        assert_eq!(dev.rknpu_submit_ioctl(&mut submit), Ok(()));
+        // 任务描述符一次，每个任务的命令缓冲区各一次
+        assert_eq!(TRANSLATIONS.with(|n| n.get()), 3);
+    }
+
+    /// 只认识一个缓冲区的查询
+    struct SingleBuffer {
+        addr: u64,
+        len: usize,
+    }
+
+    impl BufferResolver for SingleBuffer {
+        fn buffer_len(&self, dma_addr: u64) -> Option<usize> {
+            (dma_addr == self.addr).then_some(self.len)
+        }
+    }
+
+    #[test]
+    fn task_range_checked_against_buffer_boundaries() {
+        let (mut dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        // 尾部不足一个任务的字节不计入
+        let resolver = std::boxed::Box::leak(std::boxed::Box::new(SingleBuffer {
+            addr: 0x4000_0000,
+            len: 4 * size_of::<RknpuTask>() + size_of::<RknpuTask>() - 1,
+        }));
+        dev.set_buffer_resolver(resolver);
+        let submit = |task_obj_addr, task_start, task_number| RknpuSubmit {
+            flags: SubmitFlags::PC,
+            task_start,
+            task_number,
+            task_obj_addr,
+            ..Default::default()
+        };
+
+        for (task_start, task_number) in [(0, 4), (3, 1), (1, 3), (0, 1)] {
+            assert_eq!(
+                dev.check_task_range(&submit(0x4000_0000, task_start, task_number)),
+                Ok(()),
+                "{}+{}",
+                task_start,
+                task_number
+            );
+        }
+        for (task_start, task_number) in [(4, 1), (3, 2), (0, 5), (5, 0), (u32::MAX, 1)] {
+            assert_eq!(
+                dev.check_task_range(&submit(0x4000_0000, task_start, task_number)),
+                Err(RkNpuError::InvalidInput),
+                "{}+{}",
+                task_start,
+                task_number
+            );
+        }
+        assert_eq!(
+            dev.check_task_range(&submit(0x5000_0000, 0, 1)),
+            Err(RkNpuError::InvalidTaskAddress)
+        );
+
+        // 越界的提交在触碰硬件之前被拒绝
+        let mut submit = submit(0x4000_0000, 4, 1);
+        assert_eq!(
+            dev.rknpu_submit_ioctl(&mut submit),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert!(npu.writes().is_empty());
+    }
+
+    std::thread_local! {
+        static PROGRESS: core::cell::RefCell<Vec<(u32, u32)>> =
+            const { core::cell::RefCell::new(Vec::new()) };
+    }
+
+    fn record_progress(completed: u32, total: u32) {
+        PROGRESS.with(|p| p.borrow_mut().push((completed, total)));
+    }
+
+    #[test]
+    fn progress_reported_only_when_task_status_advances() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let core = NpuCore::Npu0;
+        let status = reg(core, npu_reg::INT_STATUS);
+        let task_status = reg(core, dev.config.pc_task_status_offset as usize);
+        let done = dev.config.int_done_mask;
+
+        // 六次轮询未完成，其间任务状态只推进两次；最后一个任务的进度由完成补报
+        npu.script(status, &[0, 0, 0, 0, 0, 0, done]);
+        npu.script(task_status, &[0, 1, 1, 1, 2, 2]);
+        PROGRESS.with(|p| p.borrow_mut().clear());
+        assert_eq!(
+            dev.wait_job_done_with_progress(core, 1000, 3, record_progress),
+            Ok(done)
+        );
+        assert_eq!(
+            PROGRESS.with(|p| p.borrow().clone()),
+            vec![(1, 3), (2, 3), (3, 3)]
+        );
+
+        // 完成前已观察到全部任务完成时不重复回报
+        npu.script(status, &[0, 0, 0, done]);
+        npu.script(task_status, &[1, 3, 3]);
+        PROGRESS.with(|p| p.borrow_mut().clear());
+        assert_eq!(
+            dev.wait_job_done_with_progress(core, 1000, 3, record_progress),
+            Ok(done)
+        );
+        assert_eq!(PROGRESS.with(|p| p.borrow().clone()), vec![(1, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn core_phys_base_matches_rk3588_layout() {
+        let (dev, _, _) = mock_dev(RkBoard::Rk3588);
+        assert_eq!(dev.core_phys_base(NpuCore::Npu0), Some(0xFDAB_0000));
+        assert_eq!(dev.core_phys_base(NpuCore::Npu1), Some(0xFDAC_0000));
+        assert_eq!(dev.core_phys_base(NpuCore::Npu2), Some(0xFDAD_0000));
+
+        // 不在 core_mask 中的核心
+        let (dev, _, _) = mock_dev(RkBoard::Rk3583);
+        assert_eq!(dev.core_phys_base(NpuCore::Npu1), Some(0xFDAC_0000));
+        assert_eq!(dev.core_phys_base(NpuCore::Npu2), None);
+        let (dev, _, _) = mock_dev(RkBoard::Rk3568);
+        assert_eq!(dev.core_phys_base(NpuCore::Npu1), None);
+        assert_eq!(dev.core_phys_base(NpuCore::Npu2), None);
+    }
+
+    #[test]
+    fn drm_desc_reports_board_and_simulation() {
+        for board in RkBoard::ALL {
+            let (dev, _, _) = mock_dev(board);
+            let desc = dev.drm_desc();
+            assert!(desc.contains(board.name()), "{}", desc);
+            assert!(!desc.contains("Simulated"), "{}", desc);
+
+            let desc = dev.with_simulated(true).drm_desc();
+            assert!(desc.contains(board.name()), "{}", desc);
+            assert!(desc.ends_with(" Simulated"), "{}", desc);
+        }
+        let (dev, _, _) = mock_dev(RkBoard::Rk3588);
+        assert_eq!(dev.drm_desc(), "Rockchip RK3588 NPU");
+    }
+
+    #[test]
+    fn task_queue_depth_and_flush() {
+        let (dev, npu, _) = initialized_dev(RkBoard::Rk3588);
+        let core = NpuCore::Npu0;
+        let task_status = reg(core, dev.config.pc_task_status_offset as usize);
+
+        // 空闲核心
+        assert_eq!(dev.task_queue_depth(core), Ok(0));
+
+        // 已编程 5 个任务的作业
+        dev.jobs.begin(core, None).unwrap();
+        dev.queued_tasks[core.index()].store(5, Ordering::Release);
+        for (completed, depth) in [(0, 4), (2, 2), (4, 0), (5, 0)] {
+            npu.set(task_status, completed);
+            assert_eq!(dev.task_queue_depth(core), Ok(depth), "{}", completed);
+        }
+        assert_eq!(dev.task_queue_depth(NpuCore::Npu1), Ok(0));
+
+        npu.set(task_status, 1);
+        assert_eq!(dev.flush_task_queue(core), Ok(()));
+        assert_eq!(npu.writes_to(reg(core, npu_reg::PC_OP_EN)), vec![0]);
+        assert_eq!(dev.task_queue_depth(core), Ok(0));
+        // 等待中的提交被中止
+        assert_eq!(
+            dev.wait_int(core, dev.config.int_done_mask, 1000),
+            Err(RkNpuError::Aborted)
+        );
+    }
+
+    /// 只有一个句柄的分配器
+    struct SingleHandle {
+        handle: u32,
+        base: u64,
+        size: usize,
+    }
+
+    impl NpuAllocator for SingleHandle {
+        fn create_handle(&self, _size: usize) -> RkNpuResult<(u32, u64, u64)> {
+            Err(RkNpuError::NotSupported)
+        }
+
+        fn destroy_handle(&self, _handle: u32) -> bool {
+            false
+        }
+
+        fn get_handle(&self, handle: u32) -> RkNpuResult<(u64, usize)> {
+            if handle != self.handle {
+                return Err(RkNpuError::InvalidInput);
+            }
+            Ok((self.base, self.size))
+        }
+
+        fn user_to_kernel_addr(&self, user_addr: usize) -> RkNpuResult<VirtAddr> {
+            Ok(VirtAddr::from(user_addr))
+        }
+    }
+
+    std::thread_local! {
+        static TRANSLATED: core::cell::RefCell<Vec<usize>> =
+            const { core::cell::RefCell::new(Vec::new()) };
+    }
+
+    /// 按线程记录被转换地址的恒等地址转换
+    fn recording_identity(addr: PhysAddr) -> VirtAddr {
+        TRANSLATED.with(|t| t.borrow_mut().push(addr.as_usize()));
+        identity(addr)
+    }
+
+    #[test]
+    fn handle_sync_accepts_only_sub_ranges_of_the_buffer() {
+        let (mut dev, _, _) = mock_dev(RkBoard::Rk3588);
+        dev.dma_to_kernel = recording_identity;
+        let dir = SyncDirection::ToDevice;
+        assert_eq!(
+            dev.mem_sync_handle(7, 0, 0x100, dir),
+            Err(RkNpuError::NotSupported)
+        );
+
+        dev.set_allocator(std::boxed::Box::leak(std::boxed::Box::new(SingleHandle {
+            handle: 7,
+            base: 0x4000_0000,
+            size: 0x1000,
+        })));
+        TRANSLATED.with(|t| t.borrow_mut().clear());
+        assert_eq!(dev.mem_sync_handle(7, 0x100, 0x200, dir), Ok(()));
+        // 恰好到缓冲区末尾
+        assert_eq!(dev.mem_sync_handle(7, 0xf00, 0x100, dir), Ok(()));
+        assert_eq!(dev.mem_sync_handle(7, 0x1000, 0, dir), Ok(()));
+        assert_eq!(
+            TRANSLATED.with(|t| t.borrow().clone()),
+            vec![0x4000_0100, 0x4000_0f00]
+        );
+
+        TRANSLATED.with(|t| t.borrow_mut().clear());
+        for (offset, len) in [(0xf00, 0x101), (0x1000, 1), (0, 0x1001), (u64::MAX, 2)] {
+            assert_eq!(
+                dev.mem_sync_handle(7, offset, len, dir),
+                Err(RkNpuError::InvalidInput),
+                "0x{:x}+0x{:x}",
+                offset,
+                len
+            );
+        }
+        assert_eq!(
+            dev.mem_sync_handle(8, 0, 0x100, dir),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert!(TRANSLATED.with(|t| t.borrow().is_empty()));
+
+        // 经 MEM_SYNC 以句柄方式同步
+        let mem_sync = RknpuMemSync {
+            flags: RKNPU_MEM_SYNC_BY_HANDLE | crate::types::RKNPU_MEM_SYNC_TO_DEVICE,
+            obj_addr: 7,
+            offset: 0x800,
+            size: 0x10,
+            ..Default::default()
+        };
+        assert_eq!(dev.rknpu_mem_sync_ioctl(&mem_sync), Ok(()));
+        assert_eq!(TRANSLATED.with(|t| t.borrow().clone()), vec![0x4000_0800]);
+    }
 }
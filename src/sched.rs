@@ -0,0 +1,123 @@
+use crate::types::{NpuCore, RkNpuError, RkNpuResult, SubmitPriority};
+
+/// 核心选择策略
+///
+/// 提交未指定核心（`core_mask == 0`）或指定了多个候选核心时，
+/// 由该策略从候选核心中挑选一个执行任务。
+pub trait CoreSelector: Sync {
+    /// `candidates` 为候选核心掩码（已与板型可用核心取交集且非零），
+    /// `pending` 为各核心当前在途任务数；返回 `None` 表示没有可用核心
+    fn select_core(&self, candidates: u32, pending: [usize; 3]) -> Option<NpuCore>;
+
+    /// 考虑提交优先级的核心选择，默认忽略优先级
+    fn select_core_for(
+        &self,
+        candidates: u32,
+        pending: [usize; 3],
+        _priority: SubmitPriority,
+    ) -> Option<NpuCore> {
+        self.select_core(candidates, pending)
+    }
+}
+
+/// 默认策略：选择在途任务最少的核心，负载相同时选编号最小的核心
+///
+/// 低优先级任务在候选核心中只剩一个空闲核心时排到已忙的核心上，
+/// 把空闲核心留给普通和高优先级任务。
+pub struct LeastLoaded;
+
+impl CoreSelector for LeastLoaded {
+    fn select_core(&self, candidates: u32, pending: [usize; 3]) -> Option<NpuCore> {
+        (0..pending.len())
+            .filter_map(NpuCore::from_index)
+            .filter(|core| candidates & core.mask_bit() != 0)
+            .min_by_key(|core| pending[core.index()])
+    }
+
+    fn select_core_for(
+        &self,
+        candidates: u32,
+        pending: [usize; 3],
+        priority: SubmitPriority,
+    ) -> Option<NpuCore> {
+        let idle = (0..pending.len())
+            .filter_map(NpuCore::from_index)
+            .filter(|core| candidates & core.mask_bit() != 0 && pending[core.index()] == 0)
+            .fold(0, |mask, core| mask | core.mask_bit());
+        if priority == SubmitPriority::Low && idle.count_ones() == 1 && candidates != idle {
+            return self.select_core(candidates & !idle, pending);
+        }
+        self.select_core(candidates, pending)
+    }
+}
+
+/// 最高优先级的 nice 值
+pub const NICE_MIN: i32 = -20;
+/// 最低优先级的 nice 值
+pub const NICE_MAX: i32 = 19;
+
+/// 把 `SetProcNice` 的 `action.value` 解释为 nice 值
+///
+/// 用户态传入的是 `int`，按补码重新解释为 `i32`；超出 `NICE_MIN..=NICE_MAX` 时返回 `InvalidInput`
+pub const fn nice_from_action(value: u32) -> RkNpuResult<i32> {
+    let nice = value as i32;
+    if nice < NICE_MIN || nice > NICE_MAX {
+        return Err(RkNpuError::InvalidInput);
+    }
+    Ok(nice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nice_from_action_boundaries() {
+        assert_eq!(nice_from_action(0), Ok(0));
+        assert_eq!(nice_from_action(NICE_MAX as u32), Ok(NICE_MAX));
+        assert_eq!(nice_from_action(NICE_MIN as u32), Ok(NICE_MIN));
+        assert_eq!(nice_from_action((-1i32) as u32), Ok(-1));
+
+        assert_eq!(
+            nice_from_action((NICE_MAX + 1) as u32),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(
+            nice_from_action((NICE_MIN - 1) as u32),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(
+            nice_from_action(u32::MAX / 2),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(
+            nice_from_action(i32::MIN as u32),
+            Err(RkNpuError::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn least_loaded_gives_idle_core_to_high_priority() {
+        let all = 0b111;
+        // 只有 NPU1 空闲
+        let pending = [1, 0, 2];
+        assert_eq!(
+            LeastLoaded.select_core_for(all, pending, SubmitPriority::High),
+            Some(NpuCore::Npu1)
+        );
+        assert_eq!(
+            LeastLoaded.select_core_for(all, pending, SubmitPriority::Normal),
+            Some(NpuCore::Npu1)
+        );
+        // 低优先级把最后一个空闲核心让出来
+        assert_eq!(
+            LeastLoaded.select_core_for(all, pending, SubmitPriority::Low),
+            Some(NpuCore::Npu0)
+        );
+        // 空闲核心不止一个时低优先级照常使用
+        assert_eq!(
+            LeastLoaded.select_core_for(all, [0, 0, 2], SubmitPriority::Low),
+            Some(NpuCore::Npu0)
+        );
+    }
+}
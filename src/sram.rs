@@ -0,0 +1,202 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::types::{RkNpuError, RkNpuResult};
+
+/// 位图能管理的最大块数
+const MAX_BLOCKS: u64 = u64::BITS as u64;
+
+/// NBUF SRAM 分配器
+///
+/// 以块为单位分配，块大小为 `sram_alignment`，当 SRAM 超过 64 块时
+/// 按 `sram_alignment` 的整数倍放大块大小。每次分配占用连续的若干块，
+/// 因此可用空间按块计算，已扣除对齐造成的浪费。
+pub(crate) struct SramAllocator {
+    base: u64,
+    block_size: u64,
+    num_blocks: u64,
+    /// 已分配块位图，置位表示占用
+    used: AtomicU64,
+}
+
+impl SramAllocator {
+    pub const fn new(base: u64, size: u64, alignment: u64) -> Self {
+        let per_block = size.div_ceil(MAX_BLOCKS);
+        let block_size = if per_block <= alignment {
+            alignment
+        } else {
+            per_block.next_multiple_of(alignment)
+        };
+        Self {
+            base,
+            block_size,
+            num_blocks: size / block_size,
+            used: AtomicU64::new(0),
+        }
+    }
+
+    fn all_blocks(&self) -> u64 {
+        if self.num_blocks == MAX_BLOCKS {
+            u64::MAX
+        } else {
+            (1 << self.num_blocks) - 1
+        }
+    }
+
+    /// 可分配的总字节数
+    pub fn total(&self) -> u64 {
+        self.num_blocks * self.block_size
+    }
+
+    /// 剩余可分配的字节数（按块计算）
+    pub fn free(&self) -> u64 {
+        let used = self.used.load(Ordering::Acquire);
+        (self.num_blocks - used.count_ones() as u64) * self.block_size
+    }
+
+    /// 最大的连续空闲区域字节数，单次分配不能超过该值
+    pub fn largest_free(&self) -> u64 {
+        let free = !self.used.load(Ordering::Acquire) & self.all_blocks();
+        let (mut longest, mut run) = (0, 0);
+        for block in 0..self.num_blocks {
+            if free & (1 << block) != 0 {
+                run += 1;
+                longest = longest.max(run);
+            } else {
+                run = 0;
+            }
+        }
+        longest * self.block_size
+    }
+
+    /// 分配 `size` 字节，返回物理地址
+    pub fn alloc(&self, size: u64) -> RkNpuResult<u64> {
+        if size == 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        let blocks = size.div_ceil(self.block_size);
+        if blocks > self.num_blocks {
+            return Err(RkNpuError::OutOfMemory);
+        }
+        let mask = if blocks == MAX_BLOCKS {
+            u64::MAX
+        } else {
+            (1 << blocks) - 1
+        };
+
+        let mut used = self.used.load(Ordering::Acquire);
+        loop {
+            let start = (0..=self.num_blocks - blocks)
+                .find(|start| used & (mask << start) == 0)
+                .ok_or(RkNpuError::OutOfMemory)?;
+            match self.used.compare_exchange_weak(
+                used,
+                used | (mask << start),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(self.base + start * self.block_size),
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    /// 释放由 `alloc` 分配的区域
+    pub fn free_range(&self, addr: u64, size: u64) -> RkNpuResult<()> {
+        let offset = addr
+            .checked_sub(self.base)
+            .ok_or(RkNpuError::InvalidInput)?;
+        if !offset.is_multiple_of(self.block_size) || size == 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        let start = offset / self.block_size;
+        let blocks = size.div_ceil(self.block_size);
+        if start + blocks > self.num_blocks {
+            return Err(RkNpuError::InvalidInput);
+        }
+        let mask = if blocks == MAX_BLOCKS {
+            u64::MAX
+        } else {
+            ((1 << blocks) - 1) << start
+        };
+        self.used.fetch_and(!mask, Ordering::AcqRel);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: u64 = 0xff00_0000;
+    const BLOCK: u64 = 0x1000;
+
+    /// 16 块、每块 4KB 的分配器
+    fn allocator() -> SramAllocator {
+        SramAllocator::new(BASE, 16 * BLOCK, BLOCK)
+    }
+
+    #[test]
+    fn fragmented_free_space_limits_largest_allocation() {
+        let sram = allocator();
+        let regions: [u64; 4] = core::array::from_fn(|_| sram.alloc(4 * BLOCK).unwrap());
+        assert_eq!(
+            regions,
+            [BASE, BASE + 4 * BLOCK, BASE + 8 * BLOCK, BASE + 12 * BLOCK]
+        );
+        assert_eq!(sram.free(), 0);
+
+        sram.free_range(regions[1], 4 * BLOCK).unwrap();
+        sram.free_range(regions[3], 4 * BLOCK).unwrap();
+        assert_eq!(sram.free(), 8 * BLOCK);
+        assert_eq!(sram.largest_free(), 4 * BLOCK);
+        assert_eq!(sram.alloc(5 * BLOCK), Err(RkNpuError::OutOfMemory));
+
+        // 首次适配落在最低的空洞
+        assert_eq!(sram.alloc(BLOCK), Ok(regions[1]));
+        assert_eq!(sram.free(), 7 * BLOCK);
+        assert_eq!(sram.largest_free(), 4 * BLOCK);
+    }
+
+    #[test]
+    fn freeing_neighbours_merges_free_space() {
+        let sram = allocator();
+        let a = sram.alloc(4 * BLOCK).unwrap();
+        let b = sram.alloc(BLOCK).unwrap();
+        let _c = sram.alloc(11 * BLOCK).unwrap();
+        assert_eq!(sram.largest_free(), 0);
+
+        sram.free_range(a, 4 * BLOCK).unwrap();
+        sram.free_range(b, BLOCK).unwrap();
+        assert_eq!(sram.largest_free(), 5 * BLOCK);
+        assert_eq!(sram.alloc(5 * BLOCK), Ok(BASE));
+    }
+
+    #[test]
+    fn free_range_rejects_foreign_addresses() {
+        let sram = allocator();
+        let addr = sram.alloc(2 * BLOCK).unwrap();
+        assert_eq!(
+            sram.free_range(BASE - BLOCK, BLOCK),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(
+            sram.free_range(addr + 1, BLOCK),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(
+            sram.free_range(addr, 17 * BLOCK),
+            Err(RkNpuError::InvalidInput)
+        );
+        assert_eq!(sram.free_range(addr, 0), Err(RkNpuError::InvalidInput));
+        assert_eq!(sram.free(), 14 * BLOCK);
+    }
+
+    #[test]
+    fn full_bitmap_allocates_every_block() {
+        let sram = SramAllocator::new(BASE, MAX_BLOCKS * BLOCK, BLOCK);
+        assert_eq!(sram.alloc(sram.total()), Ok(BASE));
+        assert_eq!(sram.alloc(BLOCK), Err(RkNpuError::OutOfMemory));
+        sram.free_range(BASE, sram.total()).unwrap();
+        assert_eq!(sram.largest_free(), sram.total());
+    }
+}
@@ -0,0 +1,128 @@
+use core::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::types::NpuCore;
+
+/// 驱动统计信息快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NpuStats {
+    /// 中断处理时状态为 0 的次数（共享中断线上其他设备触发）
+    pub spurious_irqs: u64,
+    /// 中断状态中含有无法识别位的次数
+    pub unrecognized_irqs: u64,
+    /// 测量了耗时的已完成任务数，未安装时钟时始终为 0
+    pub timed_jobs: u64,
+    /// 提交到完成的最短耗时（微秒）
+    pub min_latency_us: Option<u64>,
+    /// 提交到完成的最长耗时（微秒）
+    pub max_latency_us: Option<u64>,
+    /// 所有计时任务的耗时总和（微秒）
+    pub total_latency_us: u64,
+}
+
+impl NpuStats {
+    /// 提交到完成的平均耗时（微秒），尚无计时任务时返回 `None`
+    pub fn avg_latency_us(&self) -> Option<u64> {
+        self.total_latency_us.checked_div(self.timed_jobs)
+    }
+}
+
+/// 驱动内部的统计计数器
+pub(crate) struct StatsCounters {
+    spurious_irqs: AtomicU64,
+    unrecognized_irqs: AtomicU64,
+    timed_jobs: AtomicU64,
+    min_latency_us: AtomicU64,
+    max_latency_us: AtomicU64,
+    total_latency_us: AtomicU64,
+    last_latency_us: [AtomicU64; 3],
+}
+
+impl StatsCounters {
+    pub const fn new() -> Self {
+        Self {
+            spurious_irqs: AtomicU64::new(0),
+            unrecognized_irqs: AtomicU64::new(0),
+            timed_jobs: AtomicU64::new(0),
+            min_latency_us: AtomicU64::new(u64::MAX),
+            max_latency_us: AtomicU64::new(0),
+            total_latency_us: AtomicU64::new(0),
+            last_latency_us: [const { AtomicU64::new(NO_LATENCY) }; 3],
+        }
+    }
+
+    pub fn record_spurious_irq(&self) {
+        self.spurious_irqs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_unrecognized_irq(&self) {
+        self.unrecognized_irqs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一个任务从提交到完成的耗时
+    pub fn record_latency(&self, core: NpuCore, elapsed_us: u64) {
+        self.timed_jobs.fetch_add(1, Ordering::Relaxed);
+        self.min_latency_us.fetch_min(elapsed_us, Ordering::Relaxed);
+        self.max_latency_us.fetch_max(elapsed_us, Ordering::Relaxed);
+        self.total_latency_us
+            .fetch_add(elapsed_us, Ordering::Relaxed);
+        self.last_latency_us[core.index()].store(elapsed_us, Ordering::Relaxed);
+    }
+
+    pub fn last_latency(&self, core: NpuCore) -> Option<u64> {
+        match self.last_latency_us[core.index()].load(Ordering::Relaxed) {
+            NO_LATENCY => None,
+            elapsed_us => Some(elapsed_us),
+        }
+    }
+
+    pub fn snapshot(&self) -> NpuStats {
+        let timed_jobs = self.timed_jobs.load(Ordering::Relaxed);
+        let timed = timed_jobs != 0;
+        NpuStats {
+            spurious_irqs: self.spurious_irqs.load(Ordering::Relaxed),
+            unrecognized_irqs: self.unrecognized_irqs.load(Ordering::Relaxed),
+            timed_jobs,
+            min_latency_us: timed.then(|| self.min_latency_us.load(Ordering::Relaxed)),
+            max_latency_us: timed.then(|| self.max_latency_us.load(Ordering::Relaxed)),
+            total_latency_us: self.total_latency_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 表示尚无耗时记录
+const NO_LATENCY: u64 = u64::MAX;
+
+/// 表示尚无周期数记录
+const NO_CYCLES: u64 = u64::MAX;
+
+/// 按核心记录任务开始时的性能计数器值和上一个任务消耗的周期数
+pub(crate) struct CycleTracker {
+    start: [AtomicU32; 3],
+    last: [AtomicU64; 3],
+}
+
+impl CycleTracker {
+    pub const fn new() -> Self {
+        Self {
+            start: [const { AtomicU32::new(0) }; 3],
+            last: [const { AtomicU64::new(NO_CYCLES) }; 3],
+        }
+    }
+
+    pub fn begin(&self, core: NpuCore, counter: u32) {
+        self.start[core.index()].store(counter, Ordering::Relaxed);
+    }
+
+    /// 记录任务结束，计数器按 32 位回绕计算差值
+    pub fn end(&self, core: NpuCore, counter: u32) {
+        let start = self.start[core.index()].load(Ordering::Relaxed);
+        self.last[core.index()].store(counter.wrapping_sub(start) as u64, Ordering::Relaxed);
+    }
+
+    pub fn last(&self, core: NpuCore) -> Option<u64> {
+        match self.last[core.index()].load(Ordering::Relaxed) {
+            NO_CYCLES => None,
+            cycles => Some(cycles),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/// 由宿主系统提供的单调时钟
+///
+/// 安装后超时和延时按实际经过的时间计算；未安装时退化为按轮询次数估算，
+/// 实际时长取决于 CPU 频率。
+pub trait Clock: Sync {
+    /// 自任意固定起点以来经过的微秒数，必须单调不减
+    fn now_us(&self) -> u64;
+}
+
+/// 无时钟时每毫秒的估算轮询次数（每次轮询约 10us）
+const POLLS_PER_MS: u64 = 100;
+
+/// 轮询截止时间
+pub(crate) struct Deadline {
+    clock: Option<&'static dyn Clock>,
+    start_us: u64,
+    timeout_us: u64,
+    polls_left: u64,
+}
+
+impl Deadline {
+    pub fn after_us(clock: Option<&'static dyn Clock>, timeout_us: u64) -> Self {
+        Self {
+            clock,
+            start_us: clock.map_or(0, |c| c.now_us()),
+            timeout_us,
+            polls_left: timeout_us.div_ceil(1000 / POLLS_PER_MS),
+        }
+    }
+
+    pub fn after_ms(clock: Option<&'static dyn Clock>, timeout_ms: u32) -> Self {
+        Self::after_us(clock, timeout_ms as u64 * 1000)
+    }
+
+    /// 自创建以来经过的微秒数，无时钟时为 `None`
+    pub fn elapsed_us(&self) -> Option<u64> {
+        self.clock.map(|c| c.now_us().saturating_sub(self.start_us))
+    }
+
+    /// 判断是否已到期，每次调用计为一次轮询
+    pub fn expired(&mut self) -> bool {
+        match self.elapsed_us() {
+            Some(elapsed) => elapsed >= self.timeout_us,
+            None => {
+                if self.polls_left == 0 {
+                    return true;
+                }
+                self.polls_left -= 1;
+                false
+            }
+        }
+    }
+}
@@ -1,31 +1,6 @@
-use core::fmt::Display;
-
-use rk3588_rs::{
-    DrmVersion,  RknpuMemCreate, RknpuMemDestroy, RknpuMemMap, RknpuSubmit,  DRM_COMMAND_BASE, DRM_IOCTL_BASE, RKNPU_ACTION, RKNPU_MEM_CREATE, RKNPU_MEM_DESTROY, RKNPU_MEM_MAP, RKNPU_SUBMIT
-};
-
-const IOC_READ: u32 = 2;
-const IOC_WRITE: u32 = 1;
-
-const fn _iowr(ty: u8, nr: u32, size: usize) -> u32 {
-    ((IOC_READ | IOC_WRITE) << 30) | ((size as u32) << 16) | ((ty as u32) << 8) | nr
-}
-
-const DRM_IOCTL_RKNPU_ACTION: u32 = _iowr(DRM_IOCTL_BASE, DRM_COMMAND_BASE + RKNPU_ACTION, 8);
-const DRM_IOCTL_RKNPU_SUBMIT: u32 = _iowr(
-    DRM_IOCTL_BASE,
-    DRM_COMMAND_BASE + RKNPU_SUBMIT,
-    core::mem::size_of::<RknpuSubmit>(),
-);
-const DRM_IOCTL_RKNPU_MEM_CREATE: u32 =
-    _iowr(DRM_IOCTL_BASE, DRM_COMMAND_BASE + RKNPU_MEM_CREATE, core::mem::size_of::<RknpuMemCreate>());
-const DRM_IOCTL_RKNPU_MEM_MAP: u32 = _iowr(DRM_IOCTL_BASE, DRM_COMMAND_BASE + RKNPU_MEM_MAP, core::mem::size_of::<RknpuMemMap>());
-const DRM_IOCTL_RKNPU_MEM_DESTROY: u32 =
-    _iowr(DRM_IOCTL_BASE, DRM_COMMAND_BASE + RKNPU_MEM_DESTROY, core::mem::size_of::<RknpuMemDestroy>());
-const DRM_IOCTL_VERSION: u32 = _iowr(DRM_IOCTL_BASE, 0x00, core::mem::size_of::<DrmVersion>());
-const DRM_IOCTL_RKNPU_MEM_SYNC: u32 =
-    _iowr(DRM_IOCTL_BASE, DRM_COMMAND_BASE + 0x05, core::mem::size_of::<RknpuMemDestroy>());
+use rk3588_rs::{RKNPU_JOB_PINGPONG, RknpuTask};
 
+pub use crate::abi::RkNpuIoctl;
 
 /// NPU 核心标识
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -54,6 +29,112 @@ impl NpuCore {
     }
 }
 
+/// 解码后的中断状态（`int_status` / `int_raw_status` / `int_mask` 共用同一位布局）
+///
+/// 每个功能块有 group0/group1 两个完成位，对应 ping-pong 的两组寄存器配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IntStatus(pub u32);
+
+impl IntStatus {
+    /// CNA feature 读取完成
+    pub const CNA_FEATURE: u32 = 0x3;
+    /// CNA weight 读取完成
+    pub const CNA_WEIGHT: u32 = 0xc;
+    /// CNA CSC 完成
+    pub const CNA_CSC: u32 = 0x30;
+    /// CORE 完成
+    pub const CORE: u32 = 0xc0;
+    /// DPU 完成，任务的最后一级输出
+    pub const DPU: u32 = 0x300;
+    /// PPU 完成
+    pub const PPU: u32 = 0xc00;
+    /// DMA 读错误
+    pub const DMA_READ_ERROR: u32 = 1 << 12;
+    /// DMA 写错误
+    pub const DMA_WRITE_ERROR: u32 = 1 << 13;
+    /// 所有错误位
+    pub const ERROR_MASK: u32 = Self::DMA_READ_ERROR | Self::DMA_WRITE_ERROR;
+    /// 所有已定义的中断位
+    pub const KNOWN_MASK: u32 = Self::CNA_FEATURE
+        | Self::CNA_WEIGHT
+        | Self::CNA_CSC
+        | Self::CORE
+        | Self::DPU
+        | Self::PPU
+        | Self::ERROR_MASK;
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// 是否包含 `mask` 中的任意一位
+    pub const fn intersects(&self, mask: u32) -> bool {
+        self.0 & mask != 0
+    }
+
+    /// 是否有 DPU 完成位
+    pub const fn is_done(&self) -> bool {
+        self.intersects(Self::DPU)
+    }
+
+    /// 是否有错误位
+    pub const fn has_error(&self) -> bool {
+        self.intersects(Self::ERROR_MASK)
+    }
+
+    /// 未定义的中断位
+    pub const fn unrecognized(&self) -> u32 {
+        self.0 & !Self::KNOWN_MASK
+    }
+}
+
+/// 解码后的 CNA 卷积缓冲区（CBUF）配置
+///
+/// CBUF 是核心内部的片上缓冲区，按 bank 在输入特征和权重之间划分；
+/// 一层的特征或权重放不进分到的 bank 时需要分块多次读入，这是部分模型
+/// 带宽占用明显偏高的常见原因。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CbufConfig {
+    /// 分给输入特征数据的 bank 数
+    pub data_banks: u32,
+    /// 分给权重的 bank 数
+    pub weight_banks: u32,
+    /// 特征数据是否跨任务复用
+    pub data_reuse: bool,
+    /// 权重是否跨任务复用
+    pub weight_reuse: bool,
+    /// 每行特征数据占用的条目数
+    pub data_entries: u32,
+}
+
+impl CbufConfig {
+    /// 由 `cna_cbuf_con0` 和 `cna_cbuf_con1` 的值解码
+    pub const fn from_regs(con0: u32, con1: u32) -> Self {
+        Self {
+            data_banks: con0 & 0xf,
+            weight_banks: (con0 >> 4) & 0xf,
+            data_reuse: con0 & (1 << 12) != 0,
+            weight_reuse: con0 & (1 << 13) != 0,
+            data_entries: con1 & 0x3fff,
+        }
+    }
+}
+
+/// 中断分类结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrqEvent {
+    /// 任务完成
+    Done,
+    /// 任务出错，`status` 为产生中断时的状态
+    Error { status: IntStatus },
+    /// 没有需要处理的中断（共享中断线上的其他设备，或非完成/错误的中间中断）
+    Spurious,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RkBoard {
     Rk3588,
@@ -64,6 +145,26 @@ pub enum RkBoard {
 }
 
 impl RkBoard {
+    /// 所有支持的板型，顺序与 [`index`](Self::index) 一致
+    pub const ALL: [RkBoard; 5] = [
+        Self::Rk3588,
+        Self::Rk3568,
+        Self::Rv1106,
+        Self::Rk3562,
+        Self::Rk3583,
+    ];
+
+    /// 板型在 [`ALL`](Self::ALL) 中的下标
+    pub const fn index(&self) -> usize {
+        match self {
+            Self::Rk3588 => 0,
+            Self::Rk3568 => 1,
+            Self::Rv1106 => 2,
+            Self::Rk3562 => 3,
+            Self::Rk3583 => 4,
+        }
+    }
+
     pub const fn num_cores(&self) -> usize {
         match self {
             Self::Rk3588 => 3,
@@ -72,6 +173,17 @@ impl RkBoard {
         }
     }
 
+    /// 芯片型号名称，如 `"RK3588"`
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Rk3588 => "RK3588",
+            Self::Rk3568 => "RK3568",
+            Self::Rv1106 => "RV1106",
+            Self::Rk3562 => "RK3562",
+            Self::Rk3583 => "RK3583",
+        }
+    }
+
     pub const fn core_mask(&self) -> u32 {
         match self {
             Self::Rk3588 => 0x7,                               // 0b111 - 3 cores
@@ -81,48 +193,6 @@ impl RkBoard {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum RkNpuIoctl {
-    DrmIoctlVersion,
-    RknpuAction,
-    RknpuMemCreate,
-    RknpuMemSync,
-    RknpuMemDestroy,
-    RknpuMemMap,
-    RknpuSubmit,
-}
-
-impl RkNpuIoctl {
-    pub const fn from_cmd(cmd: u32) -> Option<Self> {
-        match cmd {
-            DRM_IOCTL_VERSION => Some(Self::DrmIoctlVersion),
-            DRM_IOCTL_RKNPU_ACTION => Some(Self::RknpuAction),
-            DRM_IOCTL_RKNPU_MEM_CREATE => Some(Self::RknpuMemCreate),
-            DRM_IOCTL_RKNPU_MEM_SYNC => Some(Self::RknpuMemSync),
-            DRM_IOCTL_RKNPU_MEM_DESTROY => Some(Self::RknpuMemDestroy),
-            DRM_IOCTL_RKNPU_MEM_MAP => Some(Self::RknpuMemMap),
-            DRM_IOCTL_RKNPU_SUBMIT => Some(Self::RknpuSubmit),
-            _ => None,
-        }
-    }
-}
-
-impl Display for RkNpuIoctl {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let name = match self {
-            Self::DrmIoctlVersion => "DRM_IOCTL_VERSION",
-            Self::RknpuAction => "DRM_IOCTL_RKNPU_ACTION",
-            Self::RknpuMemCreate => "DRM_IOCTL_RKNPU_MEM_CREATE",
-            Self::RknpuMemSync => "DRM_IOCTL_RKNPU_MEM_SYNC",
-            Self::RknpuMemDestroy => "DRM_IOCTL_RKNPU_MEM_DESTROY",
-            Self::RknpuMemMap => "DRM_IOCTL_RKNPU_MEM_MAP",
-            Self::RknpuSubmit => "DRM_IOCTL_RKNPU_SUBMIT",
-        };
-        write!(f, "{}", name)
-    }
-}
-
-
 pub enum RknpuActionFlag {
     GetHwVersion = 0,
     GetDrvVersion = 1,
@@ -148,11 +218,272 @@ pub enum RknpuActionFlag {
     PowerOff = 21,
     GetTotalSramSize = 22,
     GetFreeSramSize = 23,
+    /// 驱动扩展：单次提交允许的最大任务数
+    ///
+    /// 上游驱动已占用 24/25（`GET/SET_IOMMU_DOMAIN_ID`），驱动私有操作码从 0x1000 开始，
+    /// 避免与上游后续新增的操作码冲突
+    GetMaxSubmitNumber = 0x1000,
 }
 
-impl From<u32> for RknpuActionFlag {
-    fn from(value: u32) -> Self {
-        match value {
+/// 提交标志：任务完成时触发输出 fence，fence 标识写回 `submit.fence_fd`
+pub const RKNPU_JOB_FENCE_OUT: u32 = 1 << 4;
+
+/// 提交标志（驱动扩展）：只做校验和寄存器值计算并打印，不写硬件也不等待完成，
+/// 用于对照 C 驱动检查命令缓冲区的解析结果
+pub const RKNPU_JOB_DRY_RUN: u32 = 1 << 31;
+
+/// `submit.timeout` 取该值时不设超时，一直等到任务完成、出现错误中断或被
+/// `abort_job` 取消。硬件挂死且没有人调用 `abort_job` 时提交者会永远阻塞，
+/// 持有锁提交时还会连带阻塞其他提交者。
+pub const RKNPU_TIMEOUT_INFINITE: u32 = u32::MAX;
+
+/// 提交优先级，决定多个候选核心时的分配顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum SubmitPriority {
+    /// 吞吐型任务，不占用最后一个空闲核心
+    Low,
+    #[default]
+    Normal,
+    /// 延迟敏感型任务
+    High,
+}
+
+/// 解析后的提交标志
+///
+/// 在 `rknpu_submit_ioctl` 入口处从 `submit.flags` 解析一次，之后通过具名方法访问。
+/// 提交总是同步等待任务完成，尚不支持非阻塞标志。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubmitFlags(u32);
+
+impl SubmitFlags {
+    /// PC 模式，由 PC 引擎按任务列表取命令
+    pub const PC: u32 = 1 << 0;
+    /// 非阻塞提交，尚不支持，[`parse`](Self::parse) 返回 `InvalidInput`
+    pub const NONBLOCK: u32 = 1 << 1;
+    /// ping-pong 模式
+    pub const PINGPONG: u32 = RKNPU_JOB_PINGPONG;
+    /// 输入 fence
+    pub const FENCE_IN: u32 = 1 << 3;
+    /// 输出 fence
+    pub const FENCE_OUT: u32 = RKNPU_JOB_FENCE_OUT;
+    /// 缓冲区已由调用者完成缓存维护，跳过命令缓冲区刷新（驱动扩展）
+    ///
+    /// 仅在设备开启 `with_coherent_submit` 时生效。误用会让 NPU 读到
+    /// 尚未写回内存的旧数据，导致难以排查的结果错误。
+    pub const COHERENT: u32 = 1 << 30;
+    /// 演练模式（驱动扩展）
+    pub const DRY_RUN: u32 = RKNPU_JOB_DRY_RUN;
+    /// 按首个任务的 `enable_mask` 设置使能寄存器，任务结束后恢复原值（驱动扩展）
+    pub const ENABLE_MASK: u32 = 1 << 27;
+    /// 提交优先级字段（驱动扩展）：0 为普通，1 为低，2 为高，3 保留
+    pub const PRIORITY_MASK: u32 = 0x3 << Self::PRIORITY_SHIFT;
+    const PRIORITY_SHIFT: u32 = 28;
+    const PRIORITY_LOW: u32 = 1;
+    const PRIORITY_HIGH: u32 = 2;
+
+    const KNOWN: u32 = Self::PC
+        | Self::NONBLOCK
+        | Self::PINGPONG
+        | Self::FENCE_IN
+        | Self::FENCE_OUT
+        | Self::COHERENT
+        | Self::DRY_RUN
+        | Self::ENABLE_MASK
+        | Self::PRIORITY_MASK;
+
+    /// 解析 `submit.flags`，含未知位、尚不支持的 `NONBLOCK` 或无效组合
+    /// （ping-pong 但非 PC 模式）时返回 `InvalidInput`
+    pub const fn parse(flags: u32) -> RkNpuResult<Self> {
+        if flags & !Self::KNOWN != 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        // 调用者会以为提交立即返回，实际却阻塞到任务完成
+        if flags & Self::NONBLOCK != 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        if flags & Self::PINGPONG != 0 && flags & Self::PC == 0 {
+            return Err(RkNpuError::InvalidInput);
+        }
+        if flags & Self::PRIORITY_MASK == Self::PRIORITY_MASK {
+            return Err(RkNpuError::InvalidInput);
+        }
+        Ok(Self(flags))
+    }
+
+    /// 不做校验直接包装，调用者保证 `flags` 能通过 [`parse`](Self::parse)
+    pub(crate) const fn from_bits_unchecked(flags: u32) -> Self {
+        Self(flags)
+    }
+
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_pc(&self) -> bool {
+        self.0 & Self::PC != 0
+    }
+
+    pub const fn is_nonblocking(&self) -> bool {
+        self.0 & Self::NONBLOCK != 0
+    }
+
+    pub const fn is_pingpong(&self) -> bool {
+        self.0 & Self::PINGPONG != 0
+    }
+
+    pub const fn has_fence_in(&self) -> bool {
+        self.0 & Self::FENCE_IN != 0
+    }
+
+    pub const fn has_fence_out(&self) -> bool {
+        self.0 & Self::FENCE_OUT != 0
+    }
+
+    pub const fn is_coherent(&self) -> bool {
+        self.0 & Self::COHERENT != 0
+    }
+
+    pub const fn is_dry_run(&self) -> bool {
+        self.0 & Self::DRY_RUN != 0
+    }
+
+    pub const fn has_enable_mask(&self) -> bool {
+        self.0 & Self::ENABLE_MASK != 0
+    }
+
+    pub const fn priority(&self) -> SubmitPriority {
+        match (self.0 & Self::PRIORITY_MASK) >> Self::PRIORITY_SHIFT {
+            Self::PRIORITY_LOW => SubmitPriority::Low,
+            Self::PRIORITY_HIGH => SubmitPriority::High,
+            _ => SubmitPriority::Normal,
+        }
+    }
+}
+
+/// 带宽优先级描述
+///
+/// 对应 `bw_priority_addr` 处的 NoC QoS 寄存器：偏移 0x0 为优先级寄存器
+/// （写通道 P0 位于 [1:0]，读通道 P1 位于 [9:8]），偏移 0x4 为仲裁模式寄存器 [1:0]。
+/// 各字段取值范围均为 0-3。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BwPriority {
+    /// 读通道优先级
+    pub read: u8,
+    /// 写通道优先级
+    pub write: u8,
+    /// 仲裁模式
+    pub priority: u8,
+}
+
+impl BwPriority {
+    /// 优先级寄存器相对 `bw_priority_addr` 的偏移
+    pub const PRIORITY_OFFSET: usize = 0x0;
+    /// 仲裁模式寄存器相对 `bw_priority_addr` 的偏移
+    pub const MODE_OFFSET: usize = 0x4;
+    /// 期望带宽寄存器相对 `bw_priority_addr` 的偏移
+    pub const EXPECT_OFFSET: usize = 0x8;
+    /// 带宽统计时间窗寄存器相对 `bw_priority_addr` 的偏移
+    pub const TW_OFFSET: usize = 0xc;
+
+    const FIELD_MASK: u32 = 0x3;
+    const WRITE_SHIFT: u32 = 0;
+    const READ_SHIFT: u32 = 8;
+
+    /// 编码为 `(优先级寄存器, 仲裁模式寄存器)`，任一字段超出 0-3 时返回 `InvalidInput`
+    pub const fn encode(&self) -> RkNpuResult<(u32, u32)> {
+        let max = Self::FIELD_MASK as u8;
+        if self.read > max || self.write > max || self.priority > max {
+            return Err(RkNpuError::InvalidInput);
+        }
+        let prio =
+            ((self.write as u32) << Self::WRITE_SHIFT) | ((self.read as u32) << Self::READ_SHIFT);
+        Ok((prio, self.priority as u32))
+    }
+
+    /// 从寄存器值解码，忽略保留位
+    pub const fn decode(prio: u32, mode: u32) -> Self {
+        Self {
+            read: ((prio >> Self::READ_SHIFT) & Self::FIELD_MASK) as u8,
+            write: ((prio >> Self::WRITE_SHIFT) & Self::FIELD_MASK) as u8,
+            priority: (mode & Self::FIELD_MASK) as u8,
+        }
+    }
+}
+
+/// 驱动视角下单个任务描述符的解码结果
+///
+/// `RknpuTask` 是 packed 结构，字段无法直接取引用；这里按普通对齐保存提交路径实际使用的字段，
+/// 便于打印和比较。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RknpuTaskView {
+    /// 命令缓冲区 DMA 地址
+    pub regcmd_addr: u64,
+    /// 命令缓冲区中的寄存器配置条数
+    pub regcfg_amount: u32,
+    /// 中断掩码
+    pub int_mask: u32,
+    /// 提交前写入的中断清除值
+    pub int_clear: u32,
+}
+
+impl From<&RknpuTask> for RknpuTaskView {
+    fn from(task: &RknpuTask) -> Self {
+        Self {
+            regcmd_addr: task.regcmd_addr,
+            regcfg_amount: task.regcfg_amount,
+            int_mask: task.int_mask,
+            int_clear: task.int_clear,
+        }
+    }
+}
+
+/// `MEM_SYNC` 标志：CPU 写入的数据交给设备读取（清理缓存）
+pub const RKNPU_MEM_SYNC_TO_DEVICE: u32 = 1 << 0;
+/// `MEM_SYNC` 标志：设备写入的数据交给 CPU 读取（无效化缓存）
+pub const RKNPU_MEM_SYNC_FROM_DEVICE: u32 = 1 << 1;
+/// `MEM_SYNC` 标志（驱动扩展）：`obj_addr` 为缓冲区句柄，`offset`/`size` 相对该缓冲区
+pub const RKNPU_MEM_SYNC_BY_HANDLE: u32 = 1 << 31;
+
+/// 缓存维护方向，由 `MEM_SYNC` 标志解码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// CPU 写入的数据交给设备读取，清理缓存（`dc cvac`）
+    ToDevice,
+    /// 设备写入的数据交给 CPU 读取，无效化缓存（`dc ivac`）
+    FromDevice,
+    /// 双向，清理并无效化缓存（`dc civac`）
+    Bidirectional,
+}
+
+impl SyncDirection {
+    /// 从 `RKNPU_MEM_SYNC_*` 标志解码，为 0 或含未知位时返回 `InvalidInput`
+    pub const fn from_flags(flags: u32) -> RkNpuResult<Self> {
+        const BOTH: u32 = RKNPU_MEM_SYNC_TO_DEVICE | RKNPU_MEM_SYNC_FROM_DEVICE;
+        match flags {
+            RKNPU_MEM_SYNC_TO_DEVICE => Ok(Self::ToDevice),
+            RKNPU_MEM_SYNC_FROM_DEVICE => Ok(Self::FromDevice),
+            BOTH => Ok(Self::Bidirectional),
+            _ => Err(RkNpuError::InvalidInput),
+        }
+    }
+}
+
+/// `MEM_CREATE` 标志：使用 NBUF SRAM 作为缓冲区，而不是从 DRAM 分配
+pub const RKNPU_MEM_TRY_ALLOC_NBUF: u32 = 1 << 11;
+
+/// NBUF SRAM 缓冲区使用的保留句柄号
+pub const RKNPU_NBUF_HANDLE: u32 = u32::MAX;
+
+/// `ActReset` 的 `action.value` 标志位：置位时执行完整的电源域下电/上电复位，
+/// 否则只执行总线复位
+pub const RKNPU_RESET_POWER_CYCLE: u32 = 0x1;
+
+impl TryFrom<u32> for RknpuActionFlag {
+    type Error = RkNpuError;
+
+    /// 未定义的操作码返回 `InvalidInput`
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
             0 => RknpuActionFlag::GetHwVersion,
             1 => RknpuActionFlag::GetDrvVersion,
             2 => RknpuActionFlag::GetFreq,
@@ -177,8 +508,9 @@ impl From<u32> for RknpuActionFlag {
             21 => RknpuActionFlag::PowerOff,
             22 => RknpuActionFlag::GetTotalSramSize,
             23 => RknpuActionFlag::GetFreeSramSize,
-            _ => panic!("Invalid RknpuActionEnum value: {}", value),
-        }
+            0x1000 => RknpuActionFlag::GetMaxSubmitNumber,
+            _ => return Err(RkNpuError::InvalidInput),
+        })
     }
 }
 
@@ -199,6 +531,243 @@ pub enum RkNpuError {
     OutOfMemory,
     NotInitialized,
     CoreUnavailable,
+    UnrecognizedInterrupt,
+    Busy,
+    /// 寄存器读数为全 0 或全 1，核心未上电或时钟未开
+    DeviceNotResponding,
+    /// 等待中的任务被 `abort_job` 取消
+    Aborted,
+    /// 连续硬件错误过多，设备已被锁定为故障状态
+    DeviceFailed,
+    /// 中断频率超过阈值，相关中断已被屏蔽，需要复位核心
+    InterruptStorm,
+}
+
+impl RkNpuError {
+    /// 同样的请求重试后是否可能成功
+    ///
+    /// 超时、忙、资源暂时不足等瞬时错误返回 `true`；参数错误、硬件不支持、
+    /// 设备状态错误等重试也不会改变结果的错误返回 `false`，重试逻辑不应重复提交。
+    pub const fn is_recoverable(&self) -> bool {
+        match self {
+            // 硬件暂时未响应，复位或等待后可能恢复
+            Self::Timeout | Self::TaskTimeout | Self::NoInterrupt => true,
+            // 提交时硬件状态异常，复位后可重试
+            Self::TaskSubmitFailed => true,
+            // 伪中断，不影响后续任务
+            Self::UnrecognizedInterrupt => true,
+            // 中断已屏蔽，复位核心后可恢复
+            Self::InterruptStorm => true,
+            // 在途任务或 SRAM 被占满，释放后可重试
+            Self::Busy | Self::OutOfMemory => true,
+            // 请求本身有误
+            Self::InvalidInput | Self::InvalidParameter | Self::InvalidTaskAddress => false,
+            // 板型或硬件不支持
+            Self::UnsupportedVersion | Self::NotSupported | Self::CoreUnavailable => false,
+            // 设备配置或状态错误，需要调用者先处理
+            Self::DomainNotFound | Self::NotInitialized | Self::DeviceNotResponding => false,
+            Self::DeviceFailed => false,
+            // 总线或 DMA 故障，盲目重试可能扩大损害
+            Self::HardwareError | Self::MemoryFault => false,
+            // 调用者主动取消
+            Self::Aborted => false,
+        }
+    }
 }
 
 pub type RkNpuResult<T> = Result<T, RkNpuError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submit_flags_parse_accepts_valid_combinations() {
+        let pc = SubmitFlags::PC;
+        for flags in [
+            0,
+            pc,
+            pc | SubmitFlags::PINGPONG,
+            pc | SubmitFlags::FENCE_IN | SubmitFlags::FENCE_OUT,
+            pc | SubmitFlags::COHERENT | SubmitFlags::DRY_RUN | SubmitFlags::ENABLE_MASK,
+        ] {
+            assert_eq!(
+                SubmitFlags::parse(flags).map(|f| f.bits()),
+                Ok(flags),
+                "0x{:x}",
+                flags
+            );
+        }
+
+        let flags =
+            SubmitFlags::parse(pc | SubmitFlags::PINGPONG | SubmitFlags::FENCE_OUT).unwrap();
+        assert!(flags.is_pc() && flags.is_pingpong() && flags.has_fence_out());
+        assert!(!flags.has_fence_in() && !flags.is_dry_run() && !flags.is_coherent());
+    }
+
+    #[test]
+    fn submit_flags_parse_rejects_invalid_combinations() {
+        // ping-pong 需要 PC 模式
+        assert_eq!(
+            SubmitFlags::parse(SubmitFlags::PINGPONG),
+            Err(RkNpuError::InvalidInput)
+        );
+        // 非阻塞提交尚不支持
+        assert_eq!(
+            SubmitFlags::parse(SubmitFlags::PC | SubmitFlags::NONBLOCK),
+            Err(RkNpuError::InvalidInput)
+        );
+        // 保留的优先级取值
+        assert_eq!(
+            SubmitFlags::parse(SubmitFlags::PC | SubmitFlags::PRIORITY_MASK),
+            Err(RkNpuError::InvalidInput)
+        );
+        // 未知位
+        for bit in [1 << 5, 1 << 6, 1 << 16, 1 << 26] {
+            assert_eq!(
+                SubmitFlags::parse(SubmitFlags::PC | bit),
+                Err(RkNpuError::InvalidInput)
+            );
+        }
+    }
+
+    #[test]
+    fn submit_flags_priority_field() {
+        let parse = |value: u32| SubmitFlags::parse(SubmitFlags::PC | (value << 28)).unwrap();
+        assert_eq!(parse(0).priority(), SubmitPriority::Normal);
+        assert_eq!(parse(1).priority(), SubmitPriority::Low);
+        assert_eq!(parse(2).priority(), SubmitPriority::High);
+    }
+
+    #[test]
+    fn action_flag_private_opcodes_avoid_upstream_range() {
+        // 24/25 是上游的 GET/SET_IOMMU_DOMAIN_ID
+        for flag in [24, 25] {
+            assert!(RknpuActionFlag::try_from(flag).is_err(), "{}", flag);
+        }
+        assert!(matches!(
+            RknpuActionFlag::try_from(0x1000),
+            Ok(RknpuActionFlag::GetMaxSubmitNumber)
+        ));
+    }
+
+    #[test]
+    fn bw_priority_encode_decode_round_trip() {
+        for read in 0..=3 {
+            for write in 0..=3 {
+                for priority in 0..=3 {
+                    let bw = BwPriority {
+                        read,
+                        write,
+                        priority,
+                    };
+                    let (prio, mode) = bw.encode().unwrap();
+                    assert_eq!(BwPriority::decode(prio, mode), bw);
+                }
+            }
+        }
+        assert_eq!(
+            BwPriority {
+                read: 2,
+                write: 1,
+                priority: 3
+            }
+            .encode(),
+            Ok((0x201, 0x3))
+        );
+    }
+
+    #[test]
+    fn bw_priority_rejects_out_of_range_fields() {
+        for bw in [
+            BwPriority {
+                read: 4,
+                ..Default::default()
+            },
+            BwPriority {
+                write: 4,
+                ..Default::default()
+            },
+            BwPriority {
+                priority: 4,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(bw.encode(), Err(RkNpuError::InvalidInput), "{:?}", bw);
+        }
+    }
+
+    #[test]
+    fn bw_priority_decode_ignores_reserved_bits() {
+        let bw = BwPriority::decode(0xffff_fdfe, 0xffff_fff1);
+        assert_eq!(
+            bw,
+            BwPriority {
+                read: 1,
+                write: 2,
+                priority: 1
+            }
+        );
+    }
+
+    #[test]
+    fn is_recoverable_for_every_variant() {
+        use RkNpuError::*;
+
+        let recoverable = [
+            Timeout,
+            TaskTimeout,
+            NoInterrupt,
+            TaskSubmitFailed,
+            UnrecognizedInterrupt,
+            InterruptStorm,
+            Busy,
+            OutOfMemory,
+        ];
+        let fatal = [
+            InvalidInput,
+            InvalidParameter,
+            InvalidTaskAddress,
+            UnsupportedVersion,
+            NotSupported,
+            CoreUnavailable,
+            DomainNotFound,
+            NotInitialized,
+            DeviceNotResponding,
+            DeviceFailed,
+            HardwareError,
+            MemoryFault,
+            Aborted,
+        ];
+        for err in recoverable {
+            assert!(err.is_recoverable(), "{:?}", err);
+        }
+        for err in fatal {
+            assert!(!err.is_recoverable(), "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn sync_direction_from_flags() {
+        assert_eq!(
+            SyncDirection::from_flags(RKNPU_MEM_SYNC_TO_DEVICE),
+            Ok(SyncDirection::ToDevice)
+        );
+        assert_eq!(
+            SyncDirection::from_flags(RKNPU_MEM_SYNC_FROM_DEVICE),
+            Ok(SyncDirection::FromDevice)
+        );
+        assert_eq!(
+            SyncDirection::from_flags(RKNPU_MEM_SYNC_TO_DEVICE | RKNPU_MEM_SYNC_FROM_DEVICE),
+            Ok(SyncDirection::Bidirectional)
+        );
+        for flags in [0, 1 << 2, RKNPU_MEM_SYNC_TO_DEVICE | 1 << 4] {
+            assert_eq!(
+                SyncDirection::from_flags(flags),
+                Err(RkNpuError::InvalidInput),
+                "0x{:x}",
+                flags
+            );
+        }
+    }
+}